@@ -8,7 +8,7 @@
 
  
  set major_version 2
- set minor_version 0
+ set minor_version 1
  set version "$major_version.$minor_version"
  
  # TODO: load rather than source ?
@@ -78,25 +78,93 @@
 package require json
 package require json::write
 
+# Version of the bluespec.json format written, see schema/bluespec.schema.json
+set format_version 1
+
 # Load module and ports
-# Convert bitindexes to JSON array
+# Convert bitindexes to a struct typedef
 proc bitindexes_to_json {typename} {
     set entries [types::createBitIndexes $typename]
     set jsonList {}
+    set total 0
     foreach e $entries {
         foreach {rawname type width max min} $e {}
         set name [string trimleft $rawname "."]
 
         set obj [json::write::object \
-            var [json::write::string $name] \
+            name [json::write::string $name] \
             type [json::write::string $type] \
+            msb $max \
+            lsb $min \
+        ]
+        lappend jsonList $obj
+        set total [expr {max($total, $max + 1)}]
+    }
+    return [json::write::object \
+        kind [json::write::string struct] \
+        width $total \
+        fields [json::write::array {*}$jsonList] \
+    ]
+}
+
+# Tagged unions: one entry per constructor with its tag, payload type and payload width.
+# Returns "" when typename is not a tagged union.
+proc union_to_json {typename} {
+    if {[catch {Bluetcl::type full $typename} full]} { return "" }
+    if {[lindex $full 0] != "TaggedUnion"} { return "" }
+    set jsonList {}
+    set tag 0
+    foreach m [lindex [lindex $full 2] 1] {
+        foreach {mtype mname} $m {}
+        set width 0
+        if {$mtype != "void"} {
+            foreach e [types::createBitIndexes $mtype] {
+                set width [expr {max($width, [lindex $e 3] + 1)}]
+            }
+        }
+        set obj [json::write::object \
+            name [json::write::string $mname] \
+            value $tag \
+            type [json::write::string $mtype] \
             width $width \
-            min $min \
-            max $max \
         ]
         lappend jsonList $obj
+        incr tag
     }
-    return [json::write::array {*}$jsonList]
+    return [json::write::object \
+        kind [json::write::string union] \
+        members [json::write::array {*}$jsonList] \
+    ]
+}
+
+# Enums: one entry per member, numbered in declaration order as bsc does by default.
+# Explicit tags (Red = 1) come from -dsyminitial. Returns "" when typename is not an enum.
+proc enum_to_json {typename} {
+    if {[catch {Bluetcl::type full $typename} full]} { return "" }
+    if {[lindex $full 0] != "Enum"} { return "" }
+    set jsonList {}
+    set tag 0
+    foreach mname [lindex [lindex $full 2] 1] {
+        lappend jsonList [json::write::object \
+            name [json::write::string $mname] \
+            value $tag \
+        ]
+        incr tag
+    }
+    return [json::write::object \
+        kind [json::write::string enum] \
+        members [json::write::array {*}$jsonList] \
+    ]
+}
+
+# Payload types of a tagged union's constructors (void arms excluded)
+proc union_member_types {typename} {
+    set types {}
+    if {[catch {Bluetcl::type full $typename} full]} { return $types }
+    foreach m [lindex [lindex $full 2] 1] {
+        if {[lindex $m 0] != "void"} { lappend types [lindex $m 0] }
+    }
+    return $types
 }
 
 # Main function to generate JSON with blocks
@@ -117,6 +185,22 @@ proc blocks_to_json {blocks  module} {
     # Precompute all typedefs at top-level
     array set typedefsJSON {}
     foreach t $allTypes {
+        set enumJson [enum_to_json $t]
+        if {$enumJson != ""} {
+            set typedefsJSON($t) $enumJson
+            continue
+        }
+        set unionJson [union_to_json $t]
+        if {$unionJson != ""} {
+            set typedefsJSON($t) $unionJson
+            # Struct-valued arms need their own layout
+            foreach mt [union_member_types $t] {
+                if {[info exists typedefsJSON($mt)]} { continue }
+                if {[catch {types::createBitIndexes $mt}]} { continue }
+                set typedefsJSON($mt) [bitindexes_to_json $mt]
+            }
+            continue
+        }
         if {[catch {types::createBitIndexes $t}]} { continue }
         set typedefsJSON($t) [bitindexes_to_json $t]
     }
@@ -131,7 +215,7 @@ proc blocks_to_json {blocks  module} {
             set pname [lindex $p 0]
             set ptype [lindex $p 1]
             lappend portJsonList [json::write::object \
-                var [json::write::string $pname] \
+                name [json::write::string $pname] \
                 type [json::write::string $ptype] \
             ]
         }
@@ -157,15 +241,22 @@ proc blocks_to_json {blocks  module} {
 }
 #set module [lindex $argv 0]
 set jsonoutput {}
+dict set jsonoutput version $format_version
+dict set jsonoutput producer [json::write::object \
+    name [json::write::string list_signals.tcl] \
+    version [json::write::string $version] \
+]
 dict set jsonoutput top [ json::write::string [lindex $argv 0]]
+set modules {}
 foreach module $argv {
 	puts $module
 Bluetcl::module load $module
 set ports [Bluetcl::submodule porttypes $module]
 # Generate JSON and write to file
 #dict set jsonoutput $module [json::write::object $module [blocks_to_json $ports  $module]]
-dict set jsonoutput $module [blocks_to_json $ports  $module]
+dict set modules $module [blocks_to_json $ports  $module]
 }
+dict set jsonoutput modules [json::write::object {*}$modules]
 set filename "bluespec.json"
 set fh [open $filename "w"]
 puts $fh [json::write::object {*}$jsonoutput]
@@ -0,0 +1,287 @@
+// Copyright: Copyright (c) 2025 Dyumnin Semiconductors. All rights reserved.
+// Author: Vijayvithal <jahagirdar.vs@gmail.com>
+// Created on: 2026-10-17
+// Description: Arbitrary width bit vectors for decoding wide fields.
+// =========================================================================
+// src/bitvec.rs
+// =========================================================================
+
+use std::fmt;
+
+// -------------------------------------------------------------------------
+// bitvec.rs: Bit Vectors
+// -------------------------------------------------------------------------
+
+/// A fully known (no `x`/`z`) value of any width, stored as 64-bit words, least significant first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitVector {
+    width: usize,
+    words: Vec<u64>,
+}
+
+impl BitVector {
+    /// A `width`-bit vector of zeros.
+    pub fn zero(width: usize) -> BitVector {
+        BitVector { width, words: vec![0; width.div_ceil(64)] }
+    }
+
+    /// Reads MSB-first binary digits as they come out of the VCD. `None` if any digit is not 0/1.
+    pub fn from_digits(digits: &[char]) -> Option<BitVector> {
+        let mut bv = BitVector::zero(digits.len());
+        for (i, digit) in digits.iter().rev().enumerate() {
+            match digit {
+                '0' => {}
+                '1' => bv.words[i / 64] |= 1 << (i % 64),
+                _ => return None,
+            }
+        }
+        Some(bv)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn bit(&self, index: usize) -> bool {
+        index < self.width && self.words[index / 64] >> (index % 64) & 1 == 1
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    /// The MSB, i.e. the sign of a two's complement value.
+    pub fn is_negative(&self) -> bool {
+        self.width > 0 && self.bit(self.width - 1)
+    }
+
+    /// Bits `msb` down to `lsb` (inclusive) as a vector of their own.
+    pub fn slice(&self, msb: usize, lsb: usize) -> BitVector {
+        let width = (msb + 1).saturating_sub(lsb);
+        let mut out = BitVector::zero(width);
+        for i in 0..width {
+            if self.bit(lsb + i) {
+                out.words[i / 64] |= 1 << (i % 64);
+            }
+        }
+        out
+    }
+
+    /// The value, if it fits in a `u64`.
+    pub fn to_u64(&self) -> Option<u64> {
+        self.words.iter().skip(1).all(|w| *w == 0).then(|| self.words.first().copied().unwrap_or(0))
+    }
+
+    /// The value, if it fits in a `u128`.
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.words.iter().skip(2).any(|w| *w != 0) {
+            return None;
+        }
+        let low = self.words.first().copied().unwrap_or(0) as u128;
+        let high = self.words.get(1).copied().unwrap_or(0) as u128;
+        Some(high << 64 | low)
+    }
+
+    /// Unsigned value as the nearest `f64` (wide values lose their low bits, not their magnitude).
+    pub fn to_f64(&self) -> f64 {
+        self.words.iter().rev().fold(0.0, |acc, w| acc * 2f64.powi(64) + *w as f64)
+    }
+
+    /// Two's complement negation, keeping the width.
+    pub fn negate(&self) -> BitVector {
+        let mut out = self.clone();
+        let mut carry = true;
+        for word in out.words.iter_mut() {
+            let (sum, overflow) = (!*word).overflowing_add(carry as u64);
+            *word = sum;
+            carry = overflow;
+        }
+        out.mask_to_width();
+        out
+    }
+
+    /// Magnitude of a two's complement value, and whether it was negative.
+    pub fn to_signed_magnitude(&self) -> (bool, BitVector) {
+        if self.is_negative() {
+            (true, self.negate())
+        } else {
+            (false, self.clone())
+        }
+    }
+
+    /// Unsigned decimal.
+    pub fn to_decimal(&self) -> String {
+        // Peel off 19 decimal digits at a time: 10^19 is the largest power of ten in a u64
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+        let mut words = self.words.clone();
+        let mut chunks = Vec::new();
+        while words.iter().any(|w| *w != 0) {
+            let mut remainder: u128 = 0;
+            for word in words.iter_mut().rev() {
+                let dividend = remainder << 64 | *word as u128;
+                *word = (dividend / CHUNK as u128) as u64;
+                remainder = dividend % CHUNK as u128;
+            }
+            chunks.push(remainder as u64);
+        }
+        match chunks.split_last() {
+            None => "0".to_string(),
+            Some((most, rest)) => {
+                let mut text = most.to_string();
+                for chunk in rest.iter().rev() {
+                    text.push_str(&format!("{:019}", chunk));
+                }
+                text
+            }
+        }
+    }
+
+    /// Two's complement signed decimal.
+    pub fn to_signed_decimal(&self) -> String {
+        match self.to_signed_magnitude() {
+            (true, magnitude) => format!("-{}", magnitude.to_decimal()),
+            (false, magnitude) => magnitude.to_decimal(),
+        }
+    }
+
+    /// Hex digits for the whole width, e.g. `0x0f3` for a 12-bit value.
+    pub fn to_hex(&self) -> String {
+        format!("0x{}", self.power_of_two_digits(4))
+    }
+
+    /// Octal digits for the whole width, e.g. `0o0363` for a 12-bit value.
+    pub fn to_octal(&self) -> String {
+        format!("0o{}", self.power_of_two_digits(3))
+    }
+
+    /// Binary digits for the whole width, e.g. `0b000011110011`.
+    pub fn to_binary(&self) -> String {
+        format!("0b{}", self.power_of_two_digits(1))
+    }
+
+    fn power_of_two_digits(&self, bits_per_digit: usize) -> String {
+        let count = self.width.div_ceil(bits_per_digit).max(1);
+        (0..count).rev()
+            .map(|n| {
+                let digit = (0..bits_per_digit).filter(|b| self.bit(n * bits_per_digit + b)).fold(0, |acc, b| acc | 1 << b);
+                char::from_digit(digit, 16).unwrap_or('?')
+            })
+            .collect()
+    }
+
+    fn mask_to_width(&mut self) {
+        if !self.width.is_multiple_of(64)
+            && let Some(last) = self.words.last_mut()
+        {
+            *last &= (1u64 << (self.width % 64)) - 1;
+        }
+    }
+}
+
+impl fmt::Display for BitVector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_decimal())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bits(digits: &str) -> BitVector {
+        BitVector::from_digits(&digits.chars().collect::<Vec<_>>()).unwrap()
+    }
+
+    /// A `width`-bit vector holding `value`, which must fit in 128 bits.
+    fn value(width: usize, value: u128) -> BitVector {
+        let digits: String = (0..width).rev().map(|i| if i < 128 && value >> i & 1 == 1 { '1' } else { '0' }).collect();
+        bits(&digits)
+    }
+
+    /// The most negative `width`-bit value: only the MSB set.
+    fn most_negative(width: usize) -> BitVector {
+        bits(&format!("1{}", "0".repeat(width - 1)))
+    }
+
+    #[test]
+    fn rejects_unknown_digits() {
+        assert_eq!(BitVector::from_digits(&['1', 'x', '0']), None);
+        assert_eq!(BitVector::from_digits(&['z']), None);
+    }
+
+    #[test]
+    fn decimal() {
+        assert_eq!(bits("0").to_decimal(), "0");
+        assert_eq!(bits("1").to_decimal(), "1");
+        assert_eq!(value(64, u64::MAX as u128).to_decimal(), "18446744073709551615");
+        assert_eq!(value(65, 1 << 64).to_decimal(), "18446744073709551616");
+        assert_eq!(value(128, u128::MAX).to_decimal(), u128::MAX.to_string());
+        // 2^130, past u128
+        assert_eq!(bits(&format!("1{}", "0".repeat(130))).to_decimal(), "1361129467683753853853498429727072845824");
+        // A chunk boundary with zeros in the low chunk: 10^19
+        assert_eq!(value(70, 10_000_000_000_000_000_000).to_decimal(), "10000000000000000000");
+    }
+
+    #[test]
+    fn negate() {
+        assert_eq!(bits("1").negate(), bits("1"));
+        assert_eq!(bits("0").negate(), bits("0"));
+        assert_eq!(value(64, 1).negate(), value(64, u64::MAX as u128));
+        assert_eq!(value(65, 1).negate(), bits(&"1".repeat(65)));
+        assert_eq!(value(130, 5).negate().negate(), value(130, 5));
+        assert_eq!(most_negative(65).negate(), most_negative(65));
+    }
+
+    #[test]
+    fn signed_decimal() {
+        assert_eq!(bits("1").to_signed_decimal(), "-1");
+        assert_eq!(bits("0").to_signed_decimal(), "0");
+        assert_eq!(most_negative(8).to_signed_decimal(), "-128");
+        assert_eq!(value(8, 127).to_signed_decimal(), "127");
+        assert_eq!(most_negative(64).to_signed_decimal(), i64::MIN.to_string());
+        assert_eq!(value(64, u64::MAX as u128).to_signed_decimal(), "-1");
+        assert_eq!(most_negative(65).to_signed_decimal(), "-18446744073709551616");
+        assert_eq!(most_negative(128).to_signed_decimal(), i128::MIN.to_string());
+        assert_eq!(most_negative(129).to_signed_decimal(), "-340282366920938463463374607431768211456");
+        assert_eq!(bits(&"1".repeat(200)).to_signed_decimal(), "-1");
+    }
+
+    #[test]
+    fn slice() {
+        let v = value(16, 0xabcd);
+        assert_eq!(v.slice(15, 12), value(4, 0xa));
+        assert_eq!(v.slice(7, 0), value(8, 0xcd));
+        assert_eq!(v.slice(0, 0), bits("1"));
+        // Across the word boundary and out of a wide value
+        let wide = value(130, 0b11 << 63 | 1);
+        assert_eq!(wide.slice(64, 63), bits("11"));
+        assert_eq!(wide.slice(129, 65), BitVector::zero(65));
+        assert_eq!(wide.slice(64, 0).to_u128(), Some(0b11 << 63 | 1));
+        // lsb above msb gives an empty vector
+        assert_eq!(v.slice(2, 3).width(), 0);
+    }
+
+    #[test]
+    fn radix_padding() {
+        let v = value(12, 0xf3);
+        assert_eq!(v.to_hex(), "0x0f3");
+        assert_eq!(v.to_octal(), "0o0363");
+        assert_eq!(v.to_binary(), "0b000011110011");
+        // Widths that are not a multiple of the digit get a leading partial digit
+        assert_eq!(value(5, 0).to_hex(), "0x00");
+        assert_eq!(value(5, 0x1f).to_hex(), "0x1f");
+        assert_eq!(value(4, 0o17).to_octal(), "0o17");
+        assert_eq!(bits("1").to_hex(), "0x1");
+        assert_eq!(BitVector::zero(0).to_hex(), "0x0");
+        assert_eq!(value(65, 1 << 64).to_hex(), "0x10000000000000000");
+        assert_eq!(value(66, 1).to_octal(), "0o0000000000000000000001");
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(value(65, 1 << 64).to_u64(), None);
+        assert_eq!(value(65, 7).to_u64(), Some(7));
+        assert_eq!(value(200, u128::MAX).to_u128(), Some(u128::MAX));
+        assert_eq!(value(65, 1 << 64).to_f64(), 2f64.powi(64));
+    }
+}
@@ -0,0 +1,584 @@
+// Copyright: Copyright (c) 2025 Dyumnin Semiconductors. All rights reserved.
+// Author: Vijayvithal <jahagirdar.vs@gmail.com>
+// Created on: 2026-10-17
+// Description: Typedefs read straight from BSV sources, for when bluetcl output is missing.
+// =========================================================================
+// src/bsv_source.rs
+// =========================================================================
+
+use std::collections::HashMap;
+use extism_pdk::debug;
+
+use crate::design_file::{RawEnumMember, RawSegment, RawTypedef, RawUnionMember};
+use crate::helper::TypeStructure;
+use crate::type_expr::TypeExpr;
+
+// -------------------------------------------------------------------------
+// bsv_source.rs: BSV Typedef Parser
+// -------------------------------------------------------------------------
+
+/// Typedefs found in BSV sources, laid out the way bluetcl reports them in bluespec.json.
+#[derive(Debug, Default)]
+pub struct SourceTypes {
+    // Qualified names with their layouts, e.g. ("test1::Foo_st", Struct { .. }); aliases such
+    // as ("test1::Byte", Alias { target: "Bit#(8)" }) come last, after the types they may name
+    pub typedefs: Vec<(String, RawTypedef)>,
+}
+
+#[derive(Debug)]
+enum UnionArm {
+    Void,
+    Type(TypeExpr),
+    Struct(Vec<(String, TypeExpr)>),
+}
+
+#[derive(Debug)]
+enum Declaration {
+    Enum { name: String, members: Vec<(String, Option<u64>)> },
+    Struct { name: String, fields: Vec<(String, TypeExpr)> },
+    Union { name: String, arms: Vec<(String, UnionArm)> },
+    Alias { name: String, target: TypeExpr },
+}
+
+impl Declaration {
+    fn name(&self) -> &str {
+        match self {
+            Declaration::Enum { name, .. } | Declaration::Struct { name, .. } |
+            Declaration::Union { name, .. } | Declaration::Alias { name, .. } => name,
+        }
+    }
+}
+
+// --- Tokenizer ---
+
+fn tokenize(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+        } else if c == ':' && chars.get(i + 1) == Some(&':') {
+            tokens.push("::".to_string());
+            i += 2;
+        } else if c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == '\'' {
+            // Identifiers, numbers and sized literals such as 8'h3f
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || matches!(chars[i], '_' | '$' | '\'')) {
+                i += 1;
+            }
+            tokens.push(chars[start..i].iter().collect());
+        } else {
+            tokens.push(c.to_string());
+            i += 1;
+        }
+    }
+    tokens
+}
+
+/// `20`, `'h14`, `8'd20`, `5'b10100`
+fn parse_number(token: &str) -> Option<u64> {
+    let Some((_, literal)) = token.split_once('\'') else {
+        return token.replace('_', "").parse().ok();
+    };
+    let literal = literal.replace('_', "");
+    let (radix, digits) = match literal.chars().next()?.to_ascii_lowercase() {
+        'h' => (16, &literal[1..]),
+        'd' => (10, &literal[1..]),
+        'o' => (8, &literal[1..]),
+        'b' => (2, &literal[1..]),
+        _ => return None,
+    };
+    u64::from_str_radix(digits, radix).ok()
+}
+
+// --- Parser ---
+
+struct Parser {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.peek() == Some(token);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, token: &str) -> Option<()> {
+        self.eat(token).then_some(())
+    }
+
+    fn ident(&mut self) -> Option<String> {
+        let is_ident = self.peek()?.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$');
+        if is_ident { self.next() } else { None }
+    }
+
+    /// A type as written: `Bit#(8)`, `FIFO::Foo`, `Vector#(3, Maybe#(Stage))`.
+    fn type_text(&mut self) -> Option<String> {
+        if self.peek()?.starts_with(|c: char| c.is_ascii_digit()) {
+            return self.next();
+        }
+        let mut text = self.ident()?;
+        while self.eat("::") {
+            text.push_str("::");
+            text.push_str(&self.ident()?);
+        }
+        if self.eat("#") {
+            self.expect("(")?;
+            let mut args = vec![self.type_text()?];
+            while self.eat(",") {
+                args.push(self.type_text()?);
+            }
+            self.expect(")")?;
+            text.push_str(&format!("#({})", args.join(", ")));
+        }
+        Some(text)
+    }
+
+    fn type_expr(&mut self) -> Option<TypeExpr> {
+        TypeExpr::parse(&self.type_text()?)
+    }
+
+    /// `{ t a; t b; }` of a struct, or of a struct arm of a tagged union.
+    fn struct_fields(&mut self) -> Option<Vec<(String, TypeExpr)>> {
+        self.expect("{")?;
+        let mut fields = Vec::new();
+        while !self.eat("}") {
+            let field_type = self.type_expr()?;
+            fields.push((self.ident()?, field_type));
+            self.expect(";")?;
+        }
+        Some(fields)
+    }
+
+    fn enum_members(&mut self) -> Option<Vec<(String, Option<u64>)>> {
+        self.expect("{")?;
+        let mut members = Vec::new();
+        loop {
+            let name = self.ident()?;
+            let tag = if self.eat("=") { Some(parse_number(&self.next()?)?) } else { None };
+            members.push((name, tag));
+            if self.eat("}") {
+                return Some(members);
+            }
+            self.expect(",")?;
+        }
+    }
+
+    fn union_arms(&mut self) -> Option<Vec<(String, UnionArm)>> {
+        self.expect("{")?;
+        let mut arms = Vec::new();
+        while !self.eat("}") {
+            let arm = if self.eat("void") {
+                UnionArm::Void
+            } else if self.eat("struct") {
+                UnionArm::Struct(self.struct_fields()?)
+            } else {
+                UnionArm::Type(self.type_expr()?)
+            };
+            arms.push((self.ident()?, arm));
+            self.expect(";")?;
+        }
+        Some(arms)
+    }
+
+    /// Name of the declared type. `None` for polymorphic ones (`Pair#(type a)`), which
+    /// can't be laid out without knowing their parameters.
+    fn declared_name(&mut self) -> Option<String> {
+        let name = self.ident()?;
+        if self.peek() == Some("#") {
+            debug!("BSV: Skipping polymorphic typedef '{}'", name);
+            return None;
+        }
+        Some(name)
+    }
+
+    /// Skips `deriving (Bits, Eq)` and the closing `;`.
+    fn end_of_typedef(&mut self) {
+        while let Some(token) = self.next() {
+            if token == ";" {
+                return;
+            }
+        }
+    }
+
+    /// Parses the typedef following a `typedef` keyword.
+    fn typedef(&mut self) -> Option<Declaration> {
+        let declaration = if self.eat("enum") {
+            let members = self.enum_members()?;
+            Declaration::Enum { members, name: self.declared_name()? }
+        } else if self.eat("struct") {
+            let fields = self.struct_fields()?;
+            Declaration::Struct { fields, name: self.declared_name()? }
+        } else if self.eat("union") {
+            self.expect("tagged")?;
+            let arms = self.union_arms()?;
+            Declaration::Union { arms, name: self.declared_name()? }
+        } else {
+            let target = self.type_expr()?;
+            Declaration::Alias { target, name: self.declared_name()? }
+        };
+        self.end_of_typedef();
+        Some(declaration)
+    }
+}
+
+/// Every typedef in one source file that we know how to lay out.
+fn parse_declarations(text: &str) -> Vec<Declaration> {
+    let mut parser = Parser { tokens: tokenize(text), pos: 0 };
+    let mut declarations = Vec::new();
+    while let Some(token) = parser.next() {
+        if token != "typedef" {
+            continue;
+        }
+        let start = parser.pos;
+        match parser.typedef() {
+            Some(declaration) => declarations.push(declaration),
+            None => {
+                debug!("BSV: Skipping typedef at token {} ({:?})", start, parser.tokens.get(start..start + 4));
+                parser.pos = start;
+            }
+        }
+    }
+    declarations
+}
+
+/// `package test1;`, or else the file name as bsc would take it: `bsv/test1.bsv` -> `test1`.
+fn package_name(file_name: &str, tokens: &[String]) -> String {
+    if let Some(i) = tokens.iter().position(|t| t == "package")
+        && let Some(name) = tokens.get(i + 1)
+    {
+        return name.clone();
+    }
+    let base = file_name.rsplit(['/', '\\']).next().unwrap_or(file_name);
+    base.strip_suffix(".bsv").unwrap_or(base).to_string()
+}
+
+/// Packages named by `import test1 :: *;`, in order.
+fn imported_packages(tokens: &[String]) -> Vec<String> {
+    tokens.windows(3)
+        .filter(|w| w[0] == "import" && w[2] == "::")
+        .map(|w| w[1].clone())
+        .collect()
+}
+
+// --- Layout ---
+
+/// Where unqualified type names are declared, and what each package imports.
+#[derive(Debug, Default)]
+struct Scopes {
+    // `Stage` -> the packages declaring it, in file order
+    declared_in: HashMap<String, Vec<String>>,
+    // `all_types` -> `[test1, ...]`
+    imports: HashMap<String, Vec<String>>,
+}
+
+impl Scopes {
+    /// Qualifies a name used in `package`: the package's own declaration wins, then that of
+    /// the first import declaring it, then the first declaration in any file.
+    fn qualify(&self, package: &str, name: &str) -> Option<String> {
+        let declaring = self.declared_in.get(name)?;
+        let imports = self.imports.get(package).map(Vec::as_slice).unwrap_or_default();
+        let found = std::iter::once(package)
+            .chain(imports.iter().map(String::as_str))
+            .find(|candidate| declaring.iter().any(|d| d == candidate))
+            .unwrap_or(declaring[0].as_str());
+        Some(format!("{}::{}", found, name))
+    }
+}
+
+/// Qualifies the names of source typedefs used in `package` (`Foo_st` -> `test1::Foo_st`)
+/// and expands aliases.
+fn resolve(expr: &TypeExpr, package: &str, scopes: &Scopes, aliases: &HashMap<String, TypeExpr>) -> TypeExpr {
+    match expr {
+        TypeExpr::Constructor { package: qualifier, name, args } => {
+            let full_name = match qualifier {
+                Some(qualifier) => format!("{}::{}", qualifier, name),
+                None => scopes.qualify(package, name).unwrap_or_else(|| name.clone()),
+            };
+            if args.is_empty()
+                && let Some(target) = aliases.get(&full_name)
+            {
+                return target.clone();
+            }
+            let args = args.iter().map(|a| resolve(a, package, scopes, aliases)).collect();
+            match TypeExpr::parse(&full_name) {
+                Some(TypeExpr::Constructor { package, name, .. }) => TypeExpr::Constructor { package, name, args },
+                _ => expr.clone(),
+            }
+        }
+        _ => expr.clone(),
+    }
+}
+
+/// Number of bits needed for tags `0..=max_tag`, at least one.
+fn tag_bits(max_tag: u64) -> usize {
+    ((u64::BITS - max_tag.leading_zeros()) as usize).max(1)
+}
+
+fn enum_tags(members: &[(String, Option<u64>)]) -> Vec<(String, u64)> {
+    let mut next = 0;
+    members.iter().map(|(name, tag)| {
+        let tag = tag.unwrap_or(next);
+        next = tag + 1;
+        (name.clone(), tag)
+    }).collect()
+}
+
+/// Struct layout as bluetcl reports it: first field in the MSBs.
+fn struct_typedef(fields: &[(String, TypeExpr)], widths: &HashMap<String, TypeStructure>) -> Option<(RawTypedef, usize)> {
+    let field_widths: Vec<usize> = fields.iter().map(|(_, t)| t.width(widths)).collect::<Option<_>>()?;
+    let total: usize = field_widths.iter().sum();
+    let mut next_msb = total;
+    let segments = fields.iter().zip(field_widths).map(|((name, field_type), width)| {
+        let segment = RawSegment {
+            var_name: Some(name.clone()),
+            type_name: field_type.to_string(),
+            min: (next_msb - width) as isize,
+            max: next_msb as isize - 1,
+        };
+        next_msb -= width;
+        segment
+    }).collect();
+    Some((RawTypedef::Struct { width: Some(total), fields: segments }, total))
+}
+
+/// Tagged union layout as bluetcl reports it: one entry per arm, tags in declaration order.
+/// Struct arms become types of their own, named the way bluetcl names them (`Union_$Arm`).
+fn union_typedefs(
+    full_name: &str,
+    arms: &[(String, UnionArm)],
+    package: &str,
+    scopes: &Scopes,
+    aliases: &HashMap<String, TypeExpr>,
+    widths: &HashMap<String, TypeStructure>,
+) -> Option<Vec<(String, RawTypedef, usize)>> {
+    let mut laid_out = Vec::new();
+    let mut members = Vec::new();
+    let mut payload_width = 0;
+    for (tag, (arm_name, arm)) in arms.iter().enumerate() {
+        let (type_name, width) = match arm {
+            UnionArm::Void => ("void".to_string(), 0),
+            UnionArm::Type(t) => {
+                let t = resolve(t, package, scopes, aliases);
+                let width = t.width(widths)?;
+                (t.to_string(), width)
+            }
+            UnionArm::Struct(fields) => {
+                let fields: Vec<(String, TypeExpr)> = fields.iter().map(|(n, t)| (n.clone(), resolve(t, package, scopes, aliases))).collect();
+                let (typedef, width) = struct_typedef(&fields, widths)?;
+                let arm_type = format!("{}_${}", full_name, arm_name);
+                laid_out.push((arm_type.clone(), typedef, width));
+                (arm_type, width)
+            }
+        };
+        payload_width = payload_width.max(width);
+        members.push(RawUnionMember { name: arm_name.clone(), tag: tag as u64, type_name, width });
+    }
+    let tag_width = if arms.len() > 1 { tag_bits(arms.len() as u64 - 1) } else { 0 };
+    let width = tag_width + payload_width;
+    laid_out.push((full_name.to_string(), RawTypedef::Union { width: Some(width), members }, width));
+    Some(laid_out)
+}
+
+fn placeholder(total_width: usize) -> TypeStructure {
+    TypeStructure {
+        total_width,
+        segments: vec![],
+        enum_definition: None,
+        union_definition: None,
+        vector_definition: None,
+    }
+}
+
+/// Reads the typedefs of every `(file name, contents)` pair and computes their `pack` layouts.
+/// Types that use something we can't size (an unknown library type, a polymorphic typedef)
+/// are left out and reported.
+pub fn parse_bsv_sources(files: &[(String, String)]) -> SourceTypes {
+    let mut declarations: Vec<(String, Declaration)> = Vec::new();
+    let mut scopes = Scopes::default();
+    for (file_name, text) in files {
+        let tokens = tokenize(text);
+        let package = package_name(file_name, &tokens);
+        scopes.imports.entry(package.clone()).or_default().extend(imported_packages(&tokens));
+        declarations.extend(parse_declarations(text).into_iter().map(|d| (package.clone(), d)));
+    }
+    for (package, declaration) in &declarations {
+        scopes.declared_in.entry(declaration.name().to_string()).or_default().push(package.clone());
+    }
+
+    let mut aliases: HashMap<String, TypeExpr> = HashMap::new();
+    let mut alias_typedefs = Vec::new();
+    for (package, declaration) in &declarations {
+        if let Declaration::Alias { name, target } = declaration {
+            let target = resolve(target, package, &scopes, &aliases);
+            let full_name = format!("{}::{}", package, name);
+            alias_typedefs.push((full_name.clone(), RawTypedef::Alias { target: target.to_string() }));
+            aliases.insert(full_name, target);
+        }
+    }
+
+    // Lay out in dependency order: keep going while some type can be sized
+    let mut types = SourceTypes::default();
+    let mut widths: HashMap<String, TypeStructure> = HashMap::new();
+    let mut pending: Vec<&(String, Declaration)> = declarations.iter()
+        .filter(|(_, d)| !matches!(d, Declaration::Alias { .. }))
+        .collect();
+    loop {
+        let before = pending.len();
+        pending.retain(|(package, declaration)| {
+            let full_name = format!("{}::{}", package, declaration.name());
+            let laid_out: Option<Vec<(String, RawTypedef, usize)>> = match declaration {
+                Declaration::Enum { members, .. } => {
+                    let tags = enum_tags(members);
+                    let width = tag_bits(tags.iter().map(|(_, t)| *t).max().unwrap_or(0));
+                    let members = tags.into_iter().map(|(name, tag)| RawEnumMember { tag, name }).collect();
+                    Some(vec![(full_name.clone(), RawTypedef::Enum { width: Some(width), members }, width)])
+                }
+                Declaration::Struct { fields, .. } => {
+                    let fields: Vec<(String, TypeExpr)> = fields.iter().map(|(n, t)| (n.clone(), resolve(t, package, &scopes, &aliases))).collect();
+                    struct_typedef(&fields, &widths).map(|(typedef, width)| vec![(full_name.clone(), typedef, width)])
+                }
+                Declaration::Union { arms, .. } => union_typedefs(&full_name, arms, package, &scopes, &aliases, &widths),
+                Declaration::Alias { .. } => Some(vec![]),
+            };
+            match laid_out {
+                Some(laid_out) => {
+                    for (type_name, typedef, width) in laid_out {
+                        widths.insert(type_name.clone(), placeholder(width));
+                        types.typedefs.push((type_name, typedef));
+                    }
+                    false
+                }
+                None => true,
+            }
+        });
+        if pending.is_empty() || pending.len() == before {
+            break;
+        }
+    }
+    for (package, declaration) in pending {
+        debug!("BSV: Cannot lay out '{}::{}': it uses a type of unknown width", package, declaration.name());
+    }
+    types.typedefs.extend(alias_typedefs);
+    types
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST1: &str = "
+        typedef enum { FETCH, DECODE, HALT } Stage deriving (Bits, Eq);
+        typedef enum { Red = 1, Blue = 20, Green } Colors_e deriving (Bits);
+        typedef Bit#(8) Byte;
+        typedef struct { Colors_e rgb; Byte b; } Foo_st deriving (Bits);
+        typedef union tagged {
+            struct { Bit#(64) addr; Bit#(3) size; } MemRead;
+            Foo_st Pixel;
+            void Nop;
+        } Op deriving (Bits);
+        typedef struct { a x; } Pair#(type a) deriving (Bits);
+    ";
+
+    fn typedef<'a>(types: &'a SourceTypes, name: &str) -> &'a RawTypedef {
+        &types.typedefs.iter().find(|(n, _)| n == name).unwrap_or_else(|| panic!("{} not laid out", name)).1
+    }
+
+    fn field_types(types: &SourceTypes, name: &str) -> Vec<(String, isize, isize)> {
+        let RawTypedef::Struct { fields, .. } = typedef(types, name) else { panic!("{} is not a struct", name) };
+        fields.iter().map(|f| (f.type_name.clone(), f.max, f.min)).collect()
+    }
+
+    #[test]
+    fn lays_out_like_pack() {
+        let types = parse_bsv_sources(&[("bsv/test1.bsv".to_string(), TEST1.to_string())]);
+        // Aliases come after the types they may name
+        assert!(matches!(types.typedefs.last(), Some((name, RawTypedef::Alias { target }))
+            if name == "test1::Byte" && target == "Bit#(8)"));
+        // Tags continue from the last explicit one; the widest tag sets the width
+        let RawTypedef::Enum { width, members } = typedef(&types, "test1::Colors_e") else { panic!() };
+        assert_eq!(members.iter().map(|m| m.tag).collect::<Vec<_>>(), vec![1, 20, 21]);
+        assert_eq!(*width, Some(5));
+        // First field in the MSBs, aliases expanded
+        assert_eq!(field_types(&types, "test1::Foo_st"), vec![
+            ("test1::Colors_e".to_string(), 12, 8),
+            ("Bit#(8)".to_string(), 7, 0),
+        ]);
+        // Struct arms become types of their own
+        assert_eq!(field_types(&types, "test1::Op_$MemRead").len(), 2);
+        let RawTypedef::Union { width, members } = typedef(&types, "test1::Op") else { panic!() };
+        let arms: Vec<(&str, u64, usize)> = members.iter().map(|m| (m.type_name.as_str(), m.tag, m.width)).collect();
+        assert_eq!(arms, vec![("test1::Op_$MemRead", 0, 67), ("test1::Foo_st", 1, 13), ("void", 2, 0)]);
+        assert_eq!(*width, Some(69));
+        // Polymorphic typedefs are skipped
+        assert!(!types.typedefs.iter().any(|(n, _)| n.contains("Pair")));
+    }
+
+    #[test]
+    fn names_resolve_in_their_own_package_first() {
+        let other = "
+            import test1 :: *;
+            typedef enum { IF, ID, EX, MA, WB, HALT, STALL, FLUSH, X } Stage deriving (Bits);
+            typedef struct { Stage s; Foo_st f; } Packet deriving (Bits);
+        ";
+        let third = "
+            package third;
+            import test1 :: *;
+            typedef struct { Stage s; } Status deriving (Bits);
+        ";
+        let types = parse_bsv_sources(&[
+            ("test1.bsv".to_string(), TEST1.to_string()),
+            ("all_types.bsv".to_string(), other.to_string()),
+            ("src/third.bsv".to_string(), third.to_string()),
+        ]);
+        // all_types declares its own Stage; Foo_st comes from the import
+        assert_eq!(field_types(&types, "all_types::Packet"), vec![
+            ("all_types::Stage".to_string(), 16, 13),
+            ("test1::Foo_st".to_string(), 12, 0),
+        ]);
+        // third declares no Stage and imports test1's
+        assert_eq!(field_types(&types, "third::Status"), vec![("test1::Stage".to_string(), 1, 0)]);
+    }
+
+    #[test]
+    fn reports_types_of_unknown_width() {
+        let types = parse_bsv_sources(&[("t.bsv".to_string(), "typedef struct { FIFO#(Bool) f; } Q deriving (Bits);".to_string())]);
+        assert!(types.typedefs.is_empty());
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(parse_number("20"), Some(20));
+        assert_eq!(parse_number("'h14"), Some(20));
+        assert_eq!(parse_number("8'd20"), Some(20));
+        assert_eq!(parse_number("5'b1_0100"), Some(20));
+        assert_eq!(parse_number("3'o7"), Some(7));
+        assert_eq!(parse_number("'x1"), None);
+    }
+}
@@ -0,0 +1,615 @@
+// Copyright: Copyright (c) 2025 Dyumnin Semiconductors. All rights reserved.
+// Author: Vijayvithal <jahagirdar.vs@gmail.com>
+// Created on: 2026-10-17
+// Description: Reader for bluespec.json, the versioned format and the unversioned one before it.
+// =========================================================================
+// src/design_file.rs
+// =========================================================================
+
+use crate::helper::{RawBlockPort, parse_value_kind};
+use crate::type_expr::TypeExpr;
+use extism_pdk::debug;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+// -------------------------------------------------------------------------
+// design_file.rs: bluespec.json Formats and Validation
+// -------------------------------------------------------------------------
+
+// The newest bluespec.json version this plugin reads; schema/bluespec.schema.json describes it
+pub const FORMAT_VERSION: u64 = 1;
+
+// --- Layouts, whichever format they were read from ---
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RawSegment {
+    #[serde(rename = "var")]
+    pub var_name: Option<String>,
+    #[serde(rename = "type")]
+    pub type_name: String,
+    pub min: isize,
+    pub max: isize,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RawEnumMember {
+    #[serde(rename = "value")]
+    pub tag: u64,
+    #[serde(rename = "name")]
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RawUnionMember {
+    #[serde(rename = "name")]
+    pub name: String,
+    #[serde(rename = "value")]
+    pub tag: u64,
+    #[serde(rename = "type")]
+    pub type_name: String,
+    pub width: usize,
+}
+
+/// One typedef. `width` is only set when the file gave it.
+#[derive(Debug, Clone)]
+pub enum RawTypedef {
+    // Leaf fields with their `a.b` / `v[2]` paths; none for Clock, Reset and the like
+    Struct { width: Option<usize>, fields: Vec<RawSegment> },
+    Enum { width: Option<usize>, members: Vec<RawEnumMember> },
+    Union { width: Option<usize>, members: Vec<RawUnionMember> },
+    Alias { target: String },
+    Vector { length: usize, element: String },
+}
+
+impl RawTypedef {
+    /// Tells the layouts of the unversioned format apart by their keys: struct leaves have
+    /// `var`/`min`/`max`, union arms `name`/`value`/`type`/`width` and enum members `name`/`value`.
+    pub fn from_legacy(raw_value: Value) -> RawTypedef {
+        if let Ok(fields) = serde_json::from_value::<Vec<RawSegment>>(raw_value.clone()) && !fields.is_empty() {
+            return RawTypedef::Struct { width: None, fields };
+        }
+        // Must precede enums: same name/value keys plus type/width
+        if let Ok(members) = serde_json::from_value::<Vec<RawUnionMember>>(raw_value.clone()) && !members.is_empty() {
+            return RawTypedef::Union { width: None, members };
+        }
+        if let Ok(members) = serde_json::from_value::<Vec<RawEnumMember>>(raw_value) && !members.is_empty() {
+            return RawTypedef::Enum { width: None, members };
+        }
+        RawTypedef::Struct { width: None, fields: vec![] }
+    }
+
+    /// Aliases and vectors are laid out from other typedefs.
+    pub fn names_other_types(&self) -> bool {
+        matches!(self, RawTypedef::Alias { .. } | RawTypedef::Vector { .. })
+    }
+
+    /// Width as given, or as far as it follows from the layout itself.
+    pub fn width(&self) -> Option<usize> {
+        match self {
+            RawTypedef::Struct { width, fields } => width.or_else(|| fields.iter().map(|s| s.max.unsigned_abs() + 1).max()),
+            RawTypedef::Union { width, members } => width.or_else(|| {
+                let max_tag = members.iter().map(|m| m.tag).max()?;
+                Some(tag_width(max_tag).max(1) + members.iter().map(|m| m.width).max()?)
+            }),
+            RawTypedef::Enum { width, .. } => *width,
+            RawTypedef::Alias { .. } | RawTypedef::Vector { .. } => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ModuleBlockJson {
+    #[serde(rename = "type")]
+    pub type_name: String,
+    pub ports: Vec<RawBlockPort>,
+}
+
+#[derive(Debug)]
+pub struct ModuleContent {
+    pub typedefs: HashMap<String, RawTypedef>,
+    pub blocks: HashMap<String, ModuleBlockJson>,
+}
+
+#[derive(Debug, Default)]
+pub struct DesignFile {
+    // The top module; its typedefs win when modules disagree on a layout
+    pub top: String,
+    // Enums whose members are one-hot encoded, e.g. ["test1::Grant_e"]
+    pub one_hot_enums: Vec<String>,
+    // Byte-vector types shown as ASCII text, e.g. ["test1::Banner"]; `Char` always is
+    pub text_types: Vec<String>,
+    // How enum members are drawn, e.g. {"test1::Stage": {"HALT": "error"}}
+    pub enum_kinds: HashMap<String, HashMap<String, String>>,
+    pub modules: HashMap<String, ModuleContent>,
+}
+
+// --- Unversioned format: modules side by side with `top`, typedefs told apart by shape ---
+
+#[derive(Deserialize, Debug)]
+struct LegacyModule {
+    typedefs: HashMap<String, Value>,
+    blocks: HashMap<String, ModuleBlockJson>,
+}
+
+#[derive(Deserialize, Debug)]
+struct LegacyDesignFile {
+    top: String,
+    #[serde(default)]
+    one_hot_enums: Vec<String>,
+    #[serde(default)]
+    text_types: Vec<String>,
+    #[serde(default)]
+    enum_kinds: HashMap<String, HashMap<String, String>>,
+    #[serde(flatten)]
+    modules: HashMap<String, LegacyModule>,
+}
+
+// --- Version 1: schema/bluespec.schema.json ---
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct Producer {
+    name: String,
+    #[serde(default)]
+    version: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum TypedefKind {
+    Struct,
+    Enum,
+    Union,
+    Alias,
+    Vector,
+}
+
+impl TypedefKind {
+    fn name(self) -> &'static str {
+        match self {
+            TypedefKind::Struct => "struct",
+            TypedefKind::Enum => "enum",
+            TypedefKind::Union => "union",
+            TypedefKind::Alias => "alias",
+            TypedefKind::Vector => "vector",
+        }
+    }
+}
+
+// `msb`/`lsb` of one leaf field; `name` is its path, e.g. `f.rgb` or `regs[2]`
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct FieldV1 {
+    name: String,
+    #[serde(rename = "type")]
+    type_name: String,
+    msb: usize,
+    lsb: usize,
+}
+
+// Enum members carry a name and value; union arms add the payload type and width
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct MemberV1 {
+    name: String,
+    value: u64,
+    #[serde(rename = "type", default)]
+    type_name: Option<String>,
+    #[serde(default)]
+    width: Option<usize>,
+}
+
+// Which of the optional keys a typedef needs is checked per kind in `check_typedef`
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct TypedefV1 {
+    kind: TypedefKind,
+    #[serde(default)]
+    width: Option<usize>,
+    #[serde(default)]
+    fields: Option<Vec<FieldV1>>,
+    #[serde(default)]
+    members: Option<Vec<MemberV1>>,
+    #[serde(rename = "type", default)]
+    target: Option<String>,
+    #[serde(default)]
+    length: Option<usize>,
+    #[serde(default)]
+    element: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct PortV1 {
+    name: String,
+    #[serde(rename = "type")]
+    type_name: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct BlockV1 {
+    #[serde(rename = "type")]
+    type_name: String,
+    ports: Vec<PortV1>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ModuleV1 {
+    #[serde(default)]
+    typedefs: BTreeMap<String, TypedefV1>,
+    #[serde(default)]
+    blocks: BTreeMap<String, BlockV1>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct DesignFileV1 {
+    #[serde(rename = "$schema", default)]
+    _schema: Option<String>,
+    #[serde(rename = "version")]
+    _version: u64,
+    producer: Producer,
+    top: String,
+    #[serde(default)]
+    one_hot_enums: Vec<String>,
+    #[serde(default)]
+    text_types: Vec<String>,
+    #[serde(default)]
+    enum_kinds: BTreeMap<String, BTreeMap<String, String>>,
+    modules: BTreeMap<String, ModuleV1>,
+}
+
+/// Bits needed for the largest tag, as bsc sizes enum and union tags.
+fn tag_width(max_tag: u64) -> usize {
+    (u64::BITS - max_tag.leading_zeros()) as usize
+}
+
+/// Checks the keys a typedef of this kind needs or must not have, and the layout itself.
+/// Errors are pushed as `path: message`.
+fn check_typedef(path: &str, typedef: TypedefV1, errors: &mut Vec<String>) -> Option<RawTypedef> {
+    let before = errors.len();
+    let keys = [
+        ("fields", typedef.fields.is_some(), typedef.kind == TypedefKind::Struct),
+        ("members", typedef.members.is_some(), matches!(typedef.kind, TypedefKind::Enum | TypedefKind::Union)),
+        ("type", typedef.target.is_some(), typedef.kind == TypedefKind::Alias),
+        ("length", typedef.length.is_some(), typedef.kind == TypedefKind::Vector),
+        ("element", typedef.element.is_some(), typedef.kind == TypedefKind::Vector),
+        ("width", typedef.width.is_some(), matches!(typedef.kind, TypedefKind::Struct | TypedefKind::Enum | TypedefKind::Union)),
+    ];
+    for (key, present, allowed) in keys {
+        if present && !allowed {
+            errors.push(format!("{}.{}: not allowed for kind {}", path, key, typedef.kind.name()));
+        }
+    }
+    let missing = |key: &str, errors: &mut Vec<String>| errors.push(format!("{}: kind {} needs `{}`", path, typedef.kind.name(), key));
+
+    let raw_typedef = match typedef.kind {
+        TypedefKind::Struct => {
+            let Some(fields) = typedef.fields else {
+                missing("fields", errors);
+                return None;
+            };
+            for (i, field) in fields.iter().enumerate() {
+                if field.msb < field.lsb {
+                    errors.push(format!("{}.fields[{}]: msb {} is below lsb {}", path, i, field.msb, field.lsb));
+                }
+                if let Some(width) = typedef.width.filter(|w| field.msb >= *w) {
+                    errors.push(format!("{}.fields[{}]: msb {} is outside the {} bit width", path, i, field.msb, width));
+                }
+            }
+            let mut by_lsb: Vec<(usize, &FieldV1)> = fields.iter().enumerate().collect();
+            by_lsb.sort_by_key(|(_, f)| f.lsb);
+            for pair in by_lsb.windows(2) {
+                let ((i, low), (j, high)) = (pair[0], pair[1]);
+                if high.lsb <= low.msb {
+                    errors.push(format!("{}.fields[{}]: `{}` overlaps `{}` (fields[{}])", path, j, high.name, low.name, i));
+                }
+            }
+            let fields = fields.into_iter().map(|f| RawSegment {
+                var_name: Some(f.name),
+                type_name: f.type_name,
+                min: f.lsb as isize,
+                max: f.msb as isize,
+            }).collect();
+            RawTypedef::Struct { width: typedef.width, fields }
+        }
+        TypedefKind::Enum | TypedefKind::Union => {
+            let Some(members) = typedef.members else {
+                missing("members", errors);
+                return None;
+            };
+            if members.is_empty() {
+                errors.push(format!("{}.members: an {} needs at least one member", path, typedef.kind.name()));
+            }
+            for (i, member) in members.iter().enumerate() {
+                if let Some(j) = members[..i].iter().position(|m| m.name == member.name) {
+                    errors.push(format!("{}.members[{}]: name `{}` is also members[{}]", path, i, member.name, j));
+                }
+                if let Some(j) = members[..i].iter().position(|m| m.value == member.value) {
+                    errors.push(format!("{}.members[{}]: value {} is also members[{}]", path, i, member.value, j));
+                }
+                let is_union = typedef.kind == TypedefKind::Union;
+                if is_union && member.type_name.is_none() {
+                    errors.push(format!("{}.members[{}]: union members need a `type`", path, i));
+                }
+                if is_union && member.width.is_none() {
+                    errors.push(format!("{}.members[{}]: union members need a `width`", path, i));
+                }
+                if !is_union && (member.type_name.is_some() || member.width.is_some()) {
+                    errors.push(format!("{}.members[{}]: enum members have only a name and value", path, i));
+                }
+            }
+            let max_tag = members.iter().map(|m| m.value).max().unwrap_or(0);
+            let payload_width = members.iter().filter_map(|m| m.width).max().unwrap_or(0);
+            if let Some(width) = typedef.width.filter(|w| *w < tag_width(max_tag) + payload_width) {
+                let payload = if payload_width > 0 { format!(" and a {} bit payload", payload_width) } else { String::new() };
+                errors.push(format!("{}.width: {} bits cannot hold value {}{}", path, width, max_tag, payload));
+            }
+            if typedef.kind == TypedefKind::Enum {
+                let members = members.into_iter().map(|m| RawEnumMember { tag: m.value, name: m.name }).collect();
+                RawTypedef::Enum { width: typedef.width, members }
+            } else {
+                let members = members.into_iter().map(|m| RawUnionMember {
+                    name: m.name,
+                    tag: m.value,
+                    type_name: m.type_name.unwrap_or_default(),
+                    width: m.width.unwrap_or_default(),
+                }).collect();
+                RawTypedef::Union { width: typedef.width, members }
+            }
+        }
+        TypedefKind::Alias => {
+            let Some(target) = typedef.target else {
+                missing("type", errors);
+                return None;
+            };
+            if TypeExpr::parse(&target).is_none() {
+                errors.push(format!("{}.type: cannot parse type `{}`", path, target));
+            }
+            RawTypedef::Alias { target }
+        }
+        TypedefKind::Vector => {
+            let (Some(length), Some(element)) = (typedef.length, typedef.element) else {
+                missing("length` and `element", errors);
+                return None;
+            };
+            if length == 0 {
+                errors.push(format!("{}.length: a vector needs at least one element", path));
+            }
+            if TypeExpr::parse(&element).is_none() {
+                errors.push(format!("{}.element: cannot parse type `{}`", path, element));
+            }
+            RawTypedef::Vector { length, element }
+        }
+    };
+    (errors.len() == before).then_some(raw_typedef)
+}
+
+/// Checks what the schema cannot express (layouts, `top`, enum kinds) and converts the file.
+fn check_design_file(file: DesignFileV1) -> Result<DesignFile, Vec<String>> {
+    let mut errors = Vec::new();
+    if !file.modules.contains_key(&file.top) {
+        errors.push(format!("top: `{}` is not one of the modules", file.top));
+    }
+    for (type_name, rules) in &file.enum_kinds {
+        for (member, kind) in rules {
+            if parse_value_kind(kind).is_none() {
+                errors.push(format!("enum_kinds.{}.{}: unknown kind `{}`", type_name, member, kind));
+            }
+        }
+    }
+
+    let mut modules = HashMap::new();
+    for (module_name, module) in file.modules {
+        let mut typedefs = HashMap::new();
+        for (type_name, typedef) in module.typedefs {
+            let path = format!("modules.{}.typedefs.{}", module_name, type_name);
+            if let Some(raw_typedef) = check_typedef(&path, typedef, &mut errors) {
+                typedefs.insert(type_name, raw_typedef);
+            }
+        }
+        let blocks = module.blocks.into_iter().map(|(instance, block)| {
+            let ports = block.ports.into_iter().map(|p| RawBlockPort { name: p.name, type_name: p.type_name }).collect();
+            (instance, ModuleBlockJson { type_name: block.type_name, ports })
+        }).collect();
+        modules.insert(module_name, ModuleContent { typedefs, blocks });
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(DesignFile {
+        top: file.top,
+        one_hot_enums: file.one_hot_enums,
+        text_types: file.text_types,
+        enum_kinds: file.enum_kinds.into_iter().map(|(t, rules)| (t, rules.into_iter().collect())).collect(),
+        modules,
+    })
+}
+
+/// Reads bluespec.json. Files with a `version` are held to that version's schema and every
+/// problem is reported with its path; files without one are read as list_signals.tcl wrote
+/// them before the format was versioned.
+pub fn read_design_file(filename: &str, bytes: &[u8]) -> Result<DesignFile, Box<dyn std::error::Error>> {
+    let value: Value = serde_json::from_slice(bytes).map_err(|e| format!("{}: {}", filename, e))?;
+
+    let Some(version) = value.get("version") else {
+        debug!("{} has no version, reading it as the unversioned format", filename);
+        let legacy: LegacyDesignFile = serde_json::from_value(value).map_err(|e| format!("{}: {}", filename, e))?;
+        return Ok(DesignFile {
+            top: legacy.top,
+            one_hot_enums: legacy.one_hot_enums,
+            text_types: legacy.text_types,
+            enum_kinds: legacy.enum_kinds,
+            modules: legacy.modules.into_iter().map(|(module_name, module)| {
+                let typedefs = module.typedefs.into_iter().map(|(t, v)| (t, RawTypedef::from_legacy(v))).collect();
+                (module_name, ModuleContent { typedefs, blocks: module.blocks })
+            }).collect(),
+        });
+    };
+    if version.as_u64() != Some(FORMAT_VERSION) {
+        return Err(format!("{}: version: this plugin reads version {}, not {}", filename, FORMAT_VERSION, version).into());
+    }
+
+    let file: DesignFileV1 = serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_slice(bytes))
+        .map_err(|e| match e.path().to_string().as_str() {
+            // Errors in the top-level object itself (a missing key) have no path
+            "." => format!("{}: {}", filename, e.inner()),
+            path => format!("{}: {}: {}", filename, path, e.inner()),
+        })?;
+    debug!("{}: version {} from {} {}", filename, FORMAT_VERSION, file.producer.name, file.producer.version.as_deref().unwrap_or(""));
+    check_design_file(file).map_err(|errors| {
+        format!("{} does not match the version {} schema:\n  {}", filename, FORMAT_VERSION, errors.join("\n  ")).into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_V1: &str = include_str!("../bsv/bluespec_v1.json");
+    const SAMPLE_LEGACY: &str = include_str!("../bsv/bluespec.json");
+
+    /// A version 1 file with one module `mkTop` holding `typedefs`.
+    fn with_typedefs(typedefs: &str) -> String {
+        format!(r#"{{"version": 1, "producer": {{"name": "test"}}, "top": "mkTop", "modules": {{"mkTop": {{"typedefs": {}}}}}}}"#, typedefs)
+    }
+
+    fn error(text: &str) -> String {
+        match read_design_file("bluespec.json", text.as_bytes()) {
+            Ok(file) => panic!("read without errors: {:?}", file),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    fn assert_error(text: &str, expected: &str) {
+        let message = error(text);
+        assert!(message.contains(expected), "expected {:?} in {:?}", expected, message);
+    }
+
+    #[test]
+    fn sample_matches_the_unversioned_file() {
+        let v1 = read_design_file("bluespec_v1.json", SAMPLE_V1.as_bytes()).unwrap();
+        let legacy = read_design_file("bluespec.json", SAMPLE_LEGACY.as_bytes()).unwrap();
+        assert_eq!(v1.top, legacy.top);
+        let mut names: Vec<&String> = v1.modules.keys().collect();
+        names.sort();
+        let mut legacy_names: Vec<&String> = legacy.modules.keys().collect();
+        legacy_names.sort();
+        assert_eq!(names, legacy_names);
+        for (module_name, module) in &v1.modules {
+            let legacy_module = &legacy.modules[module_name];
+            for (type_name, typedef) in &module.typedefs {
+                let legacy_typedef = &legacy_module.typedefs[type_name];
+                assert_eq!(std::mem::discriminant(typedef), std::mem::discriminant(legacy_typedef), "{}", type_name);
+            }
+            assert_eq!(module.typedefs.len(), legacy_module.typedefs.len());
+            assert_eq!(module.blocks.len(), legacy_module.blocks.len());
+        }
+
+        // Structs span their fields; unions are the tag over the widest arm; enums are
+        // sized later, from the fields that use them
+        let expected = [
+            ("test1::Foo_st", Some(14)),
+            ("test1::Bar_st", Some(38)),
+            ("test1::BitLarge", Some(149)),
+            ("test1::SuperPacket", Some(356)),
+            ("Maybe#(test1::OperandValue)", Some(68)),
+            ("test1::OperandValue", Some(67)),
+            ("test1::CommitAction", Some(131)),
+            ("test1::Colors_e", None),
+        ];
+        for (type_name, width) in expected {
+            assert_eq!(v1.modules["mkTop"].typedefs[type_name].width(), width, "{}", type_name);
+            assert_eq!(legacy.modules["mkTop"].typedefs[type_name].width(), width, "{}", type_name);
+        }
+    }
+
+    #[test]
+    fn every_kind() {
+        let file = read_design_file("bluespec.json", with_typedefs(r#"{
+            "t::S": {"kind": "struct", "width": 12, "fields": [{"name": "a", "type": "Bit#(4)", "msb": 9, "lsb": 6}]},
+            "t::E": {"kind": "enum", "members": [{"name": "A", "value": 0}, {"name": "B", "value": 5}]},
+            "t::U": {"kind": "union", "width": 9, "members": [{"name": "X", "value": 0, "type": "Bit#(6)", "width": 6}, {"name": "Y", "value": 1, "type": "void", "width": 0}]},
+            "t::A": {"kind": "alias", "type": "Bit#(8)"},
+            "t::V": {"kind": "vector", "length": 4, "element": "t::A"}
+        }"#).as_bytes()).unwrap();
+        let typedefs = &file.modules["mkTop"].typedefs;
+        // Declared widths are kept, padding and all
+        assert_eq!(typedefs["t::S"].width(), Some(12));
+        assert_eq!(typedefs["t::U"].width(), Some(9));
+        assert_eq!(typedefs["t::E"].width(), None);
+        assert!(matches!(&typedefs["t::A"], RawTypedef::Alias { target } if target == "Bit#(8)"));
+        assert!(matches!(&typedefs["t::V"], RawTypedef::Vector { length: 4, element } if element == "t::A"));
+    }
+
+    #[test]
+    fn file_level_errors() {
+        assert_error(r#"{"version": 2}"#, "version: this plugin reads version 1, not 2");
+        assert_error(r#"{"version": 1, "top": "mkTop", "modules": {}}"#, "missing field `producer`");
+        assert_error(&with_typedefs("{}").replace(r#""top""#, r#""colour": 1, "top""#), "unknown field `colour`");
+        assert_error(&with_typedefs("{}").replace(r#""top": "mkTop""#, r#""top": "mkB""#), "top: `mkB` is not one of the modules");
+        assert_error(&with_typedefs("{}").replace(r#""top""#, r#""enum_kinds": {"t::E": {"A": "loud"}}, "top""#),
+            "enum_kinds.t::E.A: unknown kind `loud`");
+    }
+
+    #[test]
+    fn paths_into_typedefs() {
+        assert_error(&with_typedefs(r#"{"t::S": {"kind": "struct", "fields": [{"name": "a", "type": "Bool", "msb": 0, "lsb": 0, "min": 0}]}}"#),
+            "modules.mkTop.typedefs.t::S.fields[0].min: unknown field `min`");
+        assert_error(&with_typedefs(r#"{"t::S": {"kind": "record", "fields": []}}"#), "modules.mkTop.typedefs.t::S.kind: unknown variant `record`");
+    }
+
+    #[test]
+    fn struct_errors() {
+        let path = "modules.mkTop.typedefs.t::S";
+        assert_error(&with_typedefs(r#"{"t::S": {"kind": "struct"}}"#), &format!("{}: kind struct needs `fields`", path));
+        assert_error(&with_typedefs(r#"{"t::S": {"kind": "struct", "fields": [], "members": []}}"#), &format!("{}.members: not allowed for kind struct", path));
+        assert_error(&with_typedefs(r#"{"t::S": {"kind": "struct", "fields": [{"name": "a", "type": "Bool", "msb": 0, "lsb": 1}]}}"#),
+            &format!("{}.fields[0]: msb 0 is below lsb 1", path));
+        assert_error(&with_typedefs(r#"{"t::S": {"kind": "struct", "width": 4, "fields": [{"name": "a", "type": "Bit#(5)", "msb": 4, "lsb": 0}]}}"#),
+            &format!("{}.fields[0]: msb 4 is outside the 4 bit width", path));
+        assert_error(&with_typedefs(r#"{"t::S": {"kind": "struct", "fields": [
+                {"name": "a", "type": "Bit#(4)", "msb": 7, "lsb": 4},
+                {"name": "b", "type": "Bit#(5)", "msb": 4, "lsb": 0}]}}"#),
+            &format!("{}.fields[0]: `a` overlaps `b` (fields[1])", path));
+    }
+
+    #[test]
+    fn enum_and_union_errors() {
+        let path = "modules.mkTop.typedefs.t::E";
+        assert_error(&with_typedefs(r#"{"t::E": {"kind": "enum", "members": []}}"#), &format!("{}.members: an enum needs at least one member", path));
+        assert_error(&with_typedefs(r#"{"t::E": {"kind": "enum", "members": [{"name": "A", "value": 1}, {"name": "A", "value": 2}]}}"#),
+            &format!("{}.members[1]: name `A` is also members[0]", path));
+        assert_error(&with_typedefs(r#"{"t::E": {"kind": "enum", "members": [{"name": "A", "value": 1}, {"name": "B", "value": 1}]}}"#),
+            &format!("{}.members[1]: value 1 is also members[0]", path));
+        assert_error(&with_typedefs(r#"{"t::E": {"kind": "enum", "members": [{"name": "A", "value": 1, "width": 3}]}}"#),
+            &format!("{}.members[0]: enum members have only a name and value", path));
+        assert_error(&with_typedefs(r#"{"t::E": {"kind": "enum", "width": 2, "members": [{"name": "A", "value": 4}]}}"#),
+            &format!("{}.width: 2 bits cannot hold value 4", path));
+        assert_error(&with_typedefs(r#"{"t::E": {"kind": "union", "members": [{"name": "A", "value": 0}]}}"#),
+            &format!("{}.members[0]: union members need a `type`", path));
+        assert_error(&with_typedefs(r#"{"t::E": {"kind": "union", "width": 8, "members": [{"name": "A", "value": 1, "type": "Bit#(8)", "width": 8}]}}"#),
+            &format!("{}.width: 8 bits cannot hold value 1 and a 8 bit payload", path));
+    }
+
+    #[test]
+    fn alias_and_vector_errors() {
+        assert_error(&with_typedefs(r#"{"t::A": {"kind": "alias", "type": "Bit#(8"}}"#), "modules.mkTop.typedefs.t::A.type: cannot parse type `Bit#(8`");
+        assert_error(&with_typedefs(r#"{"t::A": {"kind": "alias", "width": 8, "type": "Bit#(8)"}}"#), "modules.mkTop.typedefs.t::A.width: not allowed for kind alias");
+        assert_error(&with_typedefs(r#"{"t::V": {"kind": "vector", "length": 0, "element": "Bool"}}"#), "modules.mkTop.typedefs.t::V.length: a vector needs at least one element");
+        assert_error(&with_typedefs(r#"{"t::V": {"kind": "vector", "length": 2}}"#), "modules.mkTop.typedefs.t::V: kind vector needs `length` and `element`");
+    }
+
+    #[test]
+    fn reports_every_problem() {
+        let message = error(&with_typedefs(r#"{
+            "t::E": {"kind": "enum", "members": []},
+            "t::V": {"kind": "vector", "length": 0, "element": "Bool"}
+        }"#));
+        assert_eq!(message.lines().count(), 3, "{}", message);
+    }
+}
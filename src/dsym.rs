@@ -0,0 +1,115 @@
+// Copyright: Copyright (c) 2025 Dyumnin Semiconductors. All rights reserved.
+// Author: Vijayvithal <jahagirdar.vs@gmail.com>
+// Created on: 2026-10-17
+// Description: Reader for the symbol table bsc dumps with -dsyminitial.
+// =========================================================================
+// src/dsym.rs
+// =========================================================================
+
+use std::collections::HashMap;
+use regex::Regex;
+
+// -------------------------------------------------------------------------
+// dsym.rs: Enum and Union Constructor Tags
+// -------------------------------------------------------------------------
+
+/// The constructors of one enum or tagged union, as bsc numbered them.
+#[derive(Debug, Default)]
+pub struct ConstructorTable {
+    // Declared with `TIdata (enum)`; otherwise a tagged union (or any other data type)
+    pub is_enum: bool,
+    // The `K` of `tag = N :: Bit K`
+    pub tag_width: usize,
+    // Unqualified constructor names with their tags, in tag order
+    pub tags: Vec<(String, u64)>,
+}
+
+// bsc names are package qualified with dots: `test1.Colors_e`
+const IDENT: &str = r"[A-Za-z][A-Za-z0-9_.]*";
+
+/// `test1.Colors_e` -> `test1::Colors_e`, the spelling bluetcl uses in bluespec.json.
+fn bluetcl_name(bsc_name: &str) -> String {
+    bsc_name.replace('.', "::")
+}
+
+/// Collects the constructor tables from a `-dsyminitial` dump:
+///
+/// `(test1.Colors_e, TypeInfo * (TIdata (enum) [test1.Red, test1.Blue]))` marks an enum, and
+/// `[ConInfo test1.Colors_e (visible) (test1.Red :>: () -> test1.Colors_e) (0 of 2, tag = 1 :: Bit 6)]`
+/// gives one constructor's tag.
+pub fn parse_dsyminitial(text: &str) -> HashMap<String, ConstructorTable> {
+    let tidata_enum = Regex::new(&format!(
+        r"\(\s*(?P<type>{IDENT})\s*,\s*TypeInfo\s+(?:(?:\*|\([^()]*\)|{IDENT})\s*){{1,3}}\(\s*TIdata\s*\(\s*enum\s*\)\s*\["
+    )).unwrap();
+    let con_info = Regex::new(&format!(
+        r"\[\s*ConInfo\s+(?P<type>{IDENT})\s*\(\s*visible\s*\)\s*\(\s*(?P<con>{IDENT})\s*:>:\s*\(\s*(?:{IDENT})?\s*\)\s*->\s*{IDENT}\s*\)\s*\(\s*\d+\s+of\s+\d+\s*,\s*tag\s*=\s*(?P<tag>\d+)\s*::\s*Bit\s+(?P<width>\d+)\s*\)\s*\]"
+    )).unwrap();
+
+    let mut tables: HashMap<String, ConstructorTable> = HashMap::new();
+    for caps in con_info.captures_iter(text) {
+        let (Ok(tag), Ok(width)) = (caps["tag"].parse::<u64>(), caps["width"].parse::<usize>()) else {
+            continue;
+        };
+        let name = caps["con"].rsplit('.').next().unwrap_or_default().to_string();
+        let table = tables.entry(bluetcl_name(&caps["type"])).or_default();
+        table.tag_width = table.tag_width.max(width);
+        if !table.tags.iter().any(|(n, _)| *n == name) {
+            table.tags.push((name, tag));
+        }
+    }
+    for caps in tidata_enum.captures_iter(text) {
+        if let Some(table) = tables.get_mut(&bluetcl_name(&caps["type"])) {
+            table.is_enum = true;
+        }
+    }
+    for table in tables.values_mut() {
+        table.tags.sort_by_key(|(_, tag)| *tag);
+    }
+    tables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DUMP: &str = r"
+ (test1.Colors_e, TypeInfo * (TIdata (enum) [test1.Red, test1.Blue])),
+ (test1.Mode_e, TypeInfo (* -> *) Foo (TIdata (enum) [test1.Idle, test1.Busy])),
+ [ConInfo test1.Colors_e (visible) (test1.Blue :>: () -> test1.Colors_e) (1 of 2, tag = 20 :: Bit 6)]
+ [ConInfo test1.Colors_e (visible) (test1.Red :>: () -> test1.Colors_e) (0 of 2, tag = 2 :: Bit 6)]
+ [ConInfo test1.Mode_e (visible) (test1.Idle :>: () -> test1.Mode_e) (0 of 2, tag = 0 :: Bit 1)]
+ [ConInfo test1.Mode_e (visible) (test1.Busy :>: () -> test1.Mode_e) (1 of 2, tag = 1 :: Bit 1)]
+ [ConInfo test1.OperandValue (visible) (test1.RegData :>: (Bit) -> test1.OperandValue) (0 of 2, tag = 1 :: Bit 2)]
+ [ConInfo test1.OperandValue (visible) (test1.InvalidOp :>: () -> test1.OperandValue) (1 of 2, tag = 0 :: Bit 2)]
+ [ConInfo test1.OperandValue (visible) (test1.InvalidOp :>: () -> test1.OperandValue) (1 of 2, tag = 0 :: Bit 2)]
+";
+
+    #[test]
+    fn enums_in_tag_order() {
+        let tables = parse_dsyminitial(DUMP);
+        let colors = &tables["test1::Colors_e"];
+        assert!(colors.is_enum);
+        assert_eq!(colors.tag_width, 6);
+        assert_eq!(colors.tags, vec![("Red".to_string(), 2), ("Blue".to_string(), 20)]);
+        // A kind annotation between TypeInfo and TIdata
+        assert!(tables["test1::Mode_e"].is_enum);
+    }
+
+    #[test]
+    fn unions_are_not_enums() {
+        let tables = parse_dsyminitial(DUMP);
+        let operand = &tables["test1::OperandValue"];
+        assert!(!operand.is_enum);
+        assert_eq!(operand.tag_width, 2);
+        // Repeated constructors are listed once
+        assert_eq!(operand.tags, vec![("InvalidOp".to_string(), 0), ("RegData".to_string(), 1)]);
+    }
+
+    #[test]
+    fn ignores_other_text() {
+        assert!(parse_dsyminitial("").is_empty());
+        assert!(parse_dsyminitial("[ConInfo test1.T (visible) (test1.A :>: () -> test1.T) (0 of 1, tag = x :: Bit 1)]").is_empty());
+        // An enum declaration without constructors has no table
+        assert!(parse_dsyminitial("(test1.E, TypeInfo * (TIdata (enum) [test1.A]))").is_empty());
+    }
+}
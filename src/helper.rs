@@ -15,7 +15,8 @@ use std::sync::RwLockReadGuard;          // <--- ADDED
 // --- Expose Data Structures (Must be pub) ---
 
 // Maps original scope path to implementation module names: {top: {mkTop: [main, top]}}
-pub static BSV_MAPS: Lazy<RwLock<HashMap<String, HashMap<String, Vec<String>>>>> = Lazy::new(|| {
+pub type ScopeMaps = HashMap<String, HashMap<String, Vec<String>>>;
+pub static BSV_MAPS: Lazy<RwLock<ScopeMaps>> = Lazy::new(|| {
     RwLock::new(HashMap::new())
 });
 
@@ -34,6 +35,11 @@ pub static BSV_LOOKUP: Lazy<RwLock<HashMap<String, TypeCategory>>> = Lazy::new(|
     RwLock::new(HashMap::new())
 });
 
+// Radix policy for Bits fields, from bluespec_format.json
+pub static BSV_FORMATS: Lazy<RwLock<FormatPolicy>> = Lazy::new(|| {
+    RwLock::new(FormatPolicy::default())
+});
+
 pub type BSVTypedefsGuard<'a> = RwLockReadGuard<'a, HashMap<String, TypeStructure>>;
 pub type BSVLookupGuard<'a> = RwLockReadGuard<'a, HashMap<String, TypeCategory>>;
 // macro_rules! lock {
@@ -52,9 +58,10 @@ pub type BSVLookupGuard<'a> = RwLockReadGuard<'a, HashMap<String, TypeCategory>>
 use serde::Deserialize;
 use std::collections::HashMap;
 use regex::Regex;
+use ecolor::Color32;
 
 use surfer_translation_types::{
-     TranslationResult,
+     TranslationResult, ValueKind,
     VariableMeta,
     // Removed StructInfo and FieldInfo imports (E0432) as VariableInfo::Compound is expected.
 };
@@ -65,15 +72,22 @@ use surfer_translation_types::{
 #[derive(Debug, Clone)]
 pub enum TypeCategory {
     Bits,
+    Int,
+    UInt,
+    FixedPoint,
+    Float,
+    Text,
     Enum,
     Bool,
     Struct,
     Union,
+    Maybe,
+    Vector,
     Interface,
     // Add more types here as needed
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TypeSegment {
     pub name: Option<String>,
     pub msb: usize,
@@ -82,25 +96,115 @@ pub struct TypeSegment {
     pub nested_structure: Option<Box<TypeStructure>>, 
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TypeStructure {
     pub total_width: usize,
     pub segments: Vec<TypeSegment>,
     pub enum_definition: Option<EnumDefinition>,
+    pub union_definition: Option<UnionDefinition>,
+    pub vector_definition: Option<VectorDefinition>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct EnumDefinition {
     pub members: HashMap<u64, String>,
+    // One-hot enums decode bit-by-bit, so several asserted bits can be reported together.
+    pub one_hot: bool,
+    // How members are drawn, e.g. {HALT: Error}; members not listed are Normal.
+    pub member_kinds: HashMap<String, ValueKind>,
+}
+
+// Vector#(n, t): segments are the elements `[0]..[n-1]`, in index order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorDefinition {
+    pub length: usize,
+    pub element_type: String,
+}
+
+// Tagged union layout: tag in the MSBs, each arm's payload left-aligned below it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnionDefinition {
+    pub tag_width: usize,
+    pub members: HashMap<u64, UnionMember>,
+    // How tags are drawn, as for enum members.
+    pub member_kinds: HashMap<String, ValueKind>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnionMember {
+    pub name: String,
+    // None for `void` arms, which carry only the tag.
+    pub payload: Option<TypeSegment>,
 }
 
 // *** FIX E0277 & Serde Attribute Error: Added #[derive(Deserialize)] ***
 #[derive(Deserialize, Debug, Clone)] 
 pub struct RawBlockPort {
     #[serde(rename = "var")]
-    name: String,
+    pub name: String,
     #[serde(rename = "type")]
-    type_name: String,
+    pub type_name: String,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Radix {
+    Binary,
+    Octal,
+    Decimal,
+    Hex,
+}
+
+// Applies to fields whose width lies within [min_width, max_width]
+#[derive(Deserialize, Debug, Clone)]
+pub struct WidthRule {
+    pub min_width: Option<usize>,
+    pub max_width: Option<usize>,
+    pub radix: Radix,
+}
+
+// Which radix a Bits field is shown in. Rules are tried by type name, then field name
+// pattern, then width; fields no rule matches are left for Surfer to format.
+#[derive(Debug, Default)]
+pub struct FormatPolicy {
+    pub by_type: HashMap<String, Radix>,
+    pub by_field: Vec<(Regex, Radix)>,
+    pub by_width: Vec<WidthRule>,
+    // Show Complex#(t) rows as `0.25 - 0.5i` rather than `Complex { rel: 0.25, img: -0.5 }`
+    pub complex_combined: bool,
+}
+
+impl FormatPolicy {
+    pub fn radix_for(&self, type_name: &str, field_name: Option<&str>, width: usize) -> Option<Radix> {
+        if let Some(radix) = self.by_type.get(type_name) {
+            return Some(*radix);
+        }
+        if let Some(name) = field_name
+            && let Some((_, radix)) = self.by_field.iter().find(|(pattern, _)| pattern.is_match(name))
+        {
+            return Some(*radix);
+        }
+        self.by_width.iter()
+            .find(|rule| rule.min_width.is_none_or(|min| width >= min) && rule.max_width.is_none_or(|max| width <= max))
+            .map(|rule| rule.radix)
+    }
+}
+
+/// Reads a kind from the enum rule table: `normal`, `weak`, `warn`, `error`, `dontcare`,
+/// or a `#rrggbb` colour.
+pub fn parse_value_kind(text: &str) -> Option<ValueKind> {
+    match text.to_lowercase().as_str() {
+        "normal" => Some(ValueKind::Normal),
+        "weak" => Some(ValueKind::Weak),
+        "warn" => Some(ValueKind::Warn),
+        "error" => Some(ValueKind::Error),
+        "dontcare" => Some(ValueKind::DontCare),
+        colour => {
+            let hex = colour.strip_prefix('#').filter(|h| h.len() == 6)?;
+            let rgb = u32::from_str_radix(hex, 16).ok()?;
+            Some(ValueKind::Custom(Color32::from_rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)))
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -130,9 +234,8 @@ const PREFERRED_PORTS: [&str; 7] = ["Q_OUT","D_OUT", "Probe","PROBE", "D_IN","WG
 
 #[derive(Debug)]
 enum SignalNameFormat {
-    FullVar(String),        // e.g., "rb" -> checks preferred ports of module "rb"
+    FullVar,                // e.g., "rb" -> checks preferred ports of module "rb"
     PortedVar(String),      // e.g., "rb_D_OUT" -> uses type of port "D_OUT" on "rb"
-    Unknown,
 }
 
 fn parse_signal_name(name: &str) -> (String, SignalNameFormat) {
@@ -149,9 +252,15 @@ fn parse_signal_name(name: &str) -> (String, SignalNameFormat) {
         }
     }
 
+    // bsc names the ports of a submodule instance `inst$port`, e.g. `aa_inst_a$EN_in`
+    if let Some((base_name, port_name)) = name.split_once('$') {
+        debug!("Matched method port {:?} of {:?}",port_name,base_name);
+        return (base_name.to_string(), SignalNameFormat::PortedVar(port_name.to_string()));
+    }
+
     // Default to FullVar
     debug!("No Matching for ={:?} ",name);
-    (name.to_string(), SignalNameFormat::FullVar(name.to_string()))
+    (name.to_string(), SignalNameFormat::FullVar)
 }
 
 // --- Core Variable Type Resolution Logic ---
@@ -177,11 +286,11 @@ pub fn get_current_module(scope_path: &[String]) -> Result<String, Box<dyn std::
     let mut current_module = module_name.ok_or("Initial scope not found in maps")?;
 
     for next_instance in scope_path.iter().skip(path_start_index) {
-        if let Some(module_data) = bsv_modules.get(&current_module) {
-             if let Some(block_def) = module_data.blocks.get(next_instance) {
-                 // Update current_module to the type name of the nested block
-                 current_module = block_def.block_type_name.clone();
-             }
+        if let Some(module_data) = bsv_modules.get(&current_module)
+            && let Some(block_def) = module_data.blocks.get(next_instance)
+        {
+            // Update current_module to the type name of the nested block
+            current_module = block_def.block_type_name.clone();
         }
     }
 
@@ -205,7 +314,12 @@ pub fn get_variable_type_name(variable: &VariableMeta<(), ()>) -> Option<String>
                 .find(|p| p.name == port_name)
                 .map(|p| p.type_name.clone())
         }
-        SignalNameFormat::FullVar(_) => {
+        SignalNameFormat::FullVar => {
+            // A synthesized submodule is shown as its methods
+            if matches!(BSV_LOOKUP.read().unwrap().get(&raw_block_def.block_type_name), Some(TypeCategory::Interface)) {
+                return Some(raw_block_def.block_type_name.clone());
+            }
+
             // Find the type of the highest priority preferred port, or first non-ignored port
             
             // a) Check preferred ports (highest priority first)
@@ -219,6 +333,55 @@ pub fn get_variable_type_name(variable: &VariableMeta<(), ()>) -> Option<String>
                 .find(|p| !IGNORED_PORTS.contains(&p.name.as_str()))
                 .map(|p| p.type_name.clone())
         }
-        SignalNameFormat::Unknown => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn width_rule(min_width: Option<usize>, max_width: Option<usize>, radix: Radix) -> WidthRule {
+        WidthRule { min_width, max_width, radix }
+    }
+
+    #[test]
+    fn radix_rules_in_order() {
+        let policy = FormatPolicy {
+            by_type: HashMap::from([("test1::Addr".to_string(), Radix::Hex)]),
+            by_field: vec![
+                (Regex::new("^addr").unwrap(), Radix::Hex),
+                (Regex::new("addr_lo").unwrap(), Radix::Binary),
+                (Regex::new("count$").unwrap(), Radix::Decimal),
+            ],
+            by_width: vec![
+                width_rule(Some(16), Some(32), Radix::Octal),
+                width_rule(Some(16), None, Radix::Hex),
+                width_rule(None, Some(4), Radix::Binary),
+            ],
+            complex_combined: false,
+        };
+        // The type beats the field name, which beats the width
+        assert_eq!(policy.radix_for("test1::Addr", Some("count"), 2), Some(Radix::Hex));
+        assert_eq!(policy.radix_for("Bit#(20)", Some("count"), 20), Some(Radix::Decimal));
+        // The first matching rule wins
+        assert_eq!(policy.radix_for("Bit#(2)", Some("addr_lo"), 2), Some(Radix::Hex));
+        assert_eq!(policy.radix_for("Bit#(20)", None, 20), Some(Radix::Octal));
+        // Width bounds are inclusive
+        assert_eq!(policy.radix_for("Bit#(16)", Some("data"), 16), Some(Radix::Octal));
+        assert_eq!(policy.radix_for("Bit#(32)", Some("data"), 32), Some(Radix::Octal));
+        assert_eq!(policy.radix_for("Bit#(33)", Some("data"), 33), Some(Radix::Hex));
+        assert_eq!(policy.radix_for("Bit#(4)", Some("data"), 4), Some(Radix::Binary));
+        assert_eq!(policy.radix_for("Bit#(8)", Some("data"), 8), None);
+    }
+
+    #[test]
+    fn value_kinds() {
+        assert!(matches!(parse_value_kind("Error"), Some(ValueKind::Error)));
+        assert!(matches!(parse_value_kind("dontcare"), Some(ValueKind::DontCare)));
+        assert!(matches!(parse_value_kind("WARN"), Some(ValueKind::Warn)));
+        assert!(matches!(parse_value_kind("#FF8000"), Some(ValueKind::Custom(c)) if c == Color32::from_rgb(0xff, 0x80, 0)));
+        assert!(parse_value_kind("#ff80").is_none());
+        assert!(parse_value_kind("#gg0000").is_none());
+        assert!(parse_value_kind("loud").is_none());
     }
 }
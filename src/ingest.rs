@@ -15,18 +15,26 @@
 // --- Import items from helper module (The "Headers") ---
 use crate::helper::{
     // Static variables
-    BSV_MAPS, BSV_MODULES, BSV_TYPEDEFS, BSV_LOOKUP,
+    BSV_MAPS, BSV_MODULES, BSV_TYPEDEFS, BSV_LOOKUP, BSV_FORMATS,
     // Data structures
     RawBlockPort, TypeSegment, TypeStructure, TypeCategory, RawBlockDefinition, ModuleData,
+    UnionDefinition, UnionMember, VectorDefinition, FormatPolicy, Radix, WidthRule,
+    parse_value_kind,
 };
 
 // ... rest of ingest.rs functions ...
 // Now `RawBlockPort`, `TypeSegment`, `TypeStructure`, `TypeCategory`,
 // `RawBlockDefinition`, `ModuleData`, and the `BSV_` statics should resolve.
-use extism_pdk::{ debug};
+use crate::type_expr::TypeExpr;
+use crate::dsym::{parse_dsyminitial, ConstructorTable};
+use crate::bsv_source::{parse_bsv_sources, SourceTypes};
+use crate::verilog::{parse_verilog, VerilogModule};
+use crate::design_file::{read_design_file, DesignFile, ModuleBlockJson, RawSegment, RawTypedef, RawUnionMember};
+use extism_pdk::{ debug, warn};
 use serde::Deserialize;
 use std::collections::HashMap;
-use serde_json::Value; 
+use surfer_translation_types::ValueKind;
+use regex::Regex;
 
 
 // macro_rules! lock {
@@ -52,55 +60,57 @@ extern "ExtismHost" {
 
 // --- JSON Deserialization Structs ---
 
-#[derive(Deserialize, Debug, Clone)] 
-struct RawSegment {
-    #[serde(rename = "var")]
-    var_name: Option<String>,
-    #[serde(rename = "type")]
-    type_name: String,
-    min: isize,
-    max: isize,
-    width: usize,
+#[derive(Deserialize, Debug)]
+struct ModuleMapping(HashMap<String, Vec<String>>);
+#[derive(Deserialize, Debug)]
+struct ModuleMapContent {
+    #[serde(flatten)]
+    maps: HashMap<String, ModuleMapping>,
 }
 
+
 #[derive(Deserialize, Debug)]
-struct RawEnumMember {
-    #[serde(rename = "value")] 
-    tag: u64,
-    #[serde(rename = "name")]
-    name: String,
+struct RawFieldRule {
+    pattern: String,
+    radix: Radix,
 }
 
-#[derive(Deserialize, Debug)]
-struct ModuleBlockJson {
-    #[serde(rename = "type")]
-    type_name: String,
-    ports: Vec<RawBlockPort>,
+#[derive(Deserialize, Debug, Default)]
+struct FormatFile {
+    #[serde(default)]
+    types: HashMap<String, Radix>,
+    #[serde(default)]
+    complex_combined: bool,
+    #[serde(default)]
+    fields: Vec<RawFieldRule>,
+    #[serde(default)]
+    widths: Vec<WidthRule>,
 }
 
 #[derive(Deserialize, Debug)]
-struct ModuleContent {
-    typedefs: HashMap<String, Value>, 
-    blocks: HashMap<String, ModuleBlockJson>, 
+struct OverrideField {
+    name: String,
+    msb: usize,
+    lsb: usize,
+    #[serde(rename = "type")]
+    type_name: String,
 }
 
+// A hand-written layout for a type with a custom `instance Bits`
 #[derive(Deserialize, Debug)]
-struct DesignFile {
-    #[serde(rename = "top")]
-    _top: String,
-    #[serde(flatten)]
-    modules: HashMap<String, ModuleContent>, 
+struct TypeOverride {
+    width: Option<usize>,
+    fields: Vec<OverrideField>,
 }
 
+// Port types of one instance, given by hand when there is no bluespec.json
 #[derive(Deserialize, Debug)]
-struct ModuleMapping(HashMap<String, Vec<String>>);
-#[derive(Deserialize, Debug)]
-struct ModuleMapContent {
-    #[serde(flatten)]
-    maps: HashMap<String, ModuleMapping>,
+struct PortHint {
+    #[serde(rename = "type")]
+    type_name: Option<String>,
+    ports: HashMap<String, String>,
 }
 
-
 // --- Data Ingestion Functions ---
 
 fn read_bsv_file(filename: &str) -> Vec<u8> {
@@ -109,18 +119,68 @@ fn read_bsv_file(filename: &str) -> Vec<u8> {
     }
 }
 
+/// Splits a bluetcl field path into its first component and the remainder:
+/// `f.rgb` -> (`f`, `rgb`), `ports[2]` -> (`ports`, `[2]`), `[3][1].b` -> (`[3]`, `[1].b`).
+fn split_field_path(path: &str) -> (String, Option<String>) {
+    let split_at = if path.starts_with('[') {
+        path.find(']').map_or(path.len(), |i| i + 1)
+    } else {
+        path.find(['.', '[']).unwrap_or(path.len())
+    };
+    let (head, rest) = path.split_at(split_at);
+    let rest = rest.strip_prefix('.').unwrap_or(rest);
+    (head.to_string(), (!rest.is_empty()).then(|| rest.to_string()))
+}
+
+/// Parses the index out of a vector element name such as `[2]`.
+pub fn vector_index(name: &str) -> Option<usize> {
+    name.strip_prefix('[')?.strip_suffix(']')?.parse().ok()
+}
+
+/// Wraps sibling segments into a structure. When every sibling is an `[i]` element the
+/// structure is a vector and its elements are ordered `[0]..[n-1]`.
+fn build_structure(mut segments: Vec<TypeSegment>, total_width: usize) -> TypeStructure {
+    let is_vector = !segments.is_empty()
+        && segments.iter().all(|s| s.name.as_deref().and_then(vector_index).is_some());
+
+    let vector_definition = if is_vector {
+        segments.sort_by_key(|s| s.name.as_deref().and_then(vector_index));
+        Some(VectorDefinition {
+            length: segments.len(),
+            element_type: segments[0].type_name.clone(),
+        })
+    } else {
+        None
+    };
+
+    TypeStructure {
+        total_width,
+        segments,
+        enum_definition: None,
+        union_definition: None,
+        vector_definition,
+    }
+}
+
 fn process_nested_segments(raw_segments: Vec<RawSegment>) -> Result<Vec<TypeSegment>, Box<dyn std::error::Error>> {
     let mut groups: HashMap<String, Vec<RawSegment>> = HashMap::new();
     let mut top_level_names: Vec<String> = Vec::new();
+    let mut has_children: Vec<String> = Vec::new();
 
-    for seg in raw_segments {
+    for mut seg in raw_segments {
         let var_name = seg.var_name.clone().unwrap_or_default();
-        let parts: Vec<&str> = var_name.splitn(2, '.').collect();
-        let top_name = parts[0].to_string();
+        let (top_name, rest) = split_field_path(&var_name);
 
         if !top_level_names.contains(&top_name) {
             top_level_names.push(top_name.clone());
         }
+        // Children carry only the remainder of their path, e.g. `ports[2]` -> `[2]`
+        if let Some(rest) = rest {
+            seg.var_name = Some(rest);
+            if !has_children.contains(&top_name) {
+                has_children.push(top_name.clone());
+            }
+        }
 
         groups.entry(top_name).or_default().push(seg);
     }
@@ -130,37 +190,23 @@ fn process_nested_segments(raw_segments: Vec<RawSegment>) -> Result<Vec<TypeSegm
     for top_name in top_level_names {
         if let Some( group) = groups.remove(&top_name) {
 
-            let is_nested = group.iter().any(|s| s.var_name.as_ref().map_or(false, |n| n.contains('.')));
-
-            if is_nested {
-                // --- NESTED CASE: Recurse and create compound segment ---
-                let remaining_segments: Vec<RawSegment> = group.into_iter().map(|mut seg| {
-                    if let Some(name) = seg.var_name.as_mut() {
-                        if let Some(dot_index) = name.find('.') {
-                            *name = name[(dot_index + 1)..].to_string();
-                        }
-                    }
-                    seg
-                }).collect();
-
-                let inner_segments = process_nested_segments(remaining_segments)?;
+            if has_children.contains(&top_name) {
+                // --- NESTED CASE: Recurse and create compound (struct or vector) segment ---
+                let inner_segments = process_nested_segments(group)?;
 
                 // Calculate bounds from ACTUAL inner segments
                 let inner_max_msb = inner_segments.iter().map(|s| s.msb).max().unwrap_or(0);
                 let inner_min_lsb = inner_segments.iter().map(|s| s.lsb).min().unwrap_or(0);
                 let inner_total_width = inner_max_msb.saturating_add(1);
 
-                let inner_structure = TypeStructure {
-                    total_width: inner_total_width,
-                    segments: inner_segments,
-                    enum_definition: None,
-                };
+                let inner_structure = build_structure(inner_segments, inner_total_width);
+                let type_name = if inner_structure.vector_definition.is_some() { "Vector" } else { "Compound" };
 
                 final_segments.push(TypeSegment {
                     name: Some(top_name),
                     msb: inner_max_msb,
                     lsb: inner_min_lsb,
-                    type_name: "Compound".to_string(),
+                    type_name: type_name.to_string(),
                     nested_structure: Some(Box::new(inner_structure)),
                 });
 
@@ -169,8 +215,8 @@ fn process_nested_segments(raw_segments: Vec<RawSegment>) -> Result<Vec<TypeSegm
                 for seg in group {
                     final_segments.push(TypeSegment {
                         name: seg.var_name,
-                        msb: seg.max.abs() as usize,
-                        lsb: seg.min.abs() as usize,
+                        msb: seg.max.unsigned_abs(),
+                        lsb: seg.min.unsigned_abs(),
                         type_name: seg.type_name,
                         nested_structure: None,
                     });
@@ -184,85 +230,570 @@ fn process_nested_segments(raw_segments: Vec<RawSegment>) -> Result<Vec<TypeSegm
 
 // src/ingest.rs (Corrected process_typedef)
 
-fn process_typedef(type_name: &str, raw_value_ref: Value, bsv_typedefs: &mut HashMap<String, TypeStructure>, bsv_lookup: &mut HashMap<String, TypeCategory>) -> Result<(), Box<dyn std::error::Error>> {
+/// Returns `T` for a `Maybe#(T)` type name (`T` may itself be unresolved, e.g. `type a`).
+pub fn maybe_payload_type(type_name: &str) -> Option<&str> {
+    type_name.strip_prefix("Maybe#(")?.strip_suffix(')').map(str::trim)
+}
 
-    if type_name.starts_with("Bit#(") || type_name == "Bool" || type_name == "Clock" || type_name == "Reset" {
-        debug!("INGEST: Explicitly marking primitive type '{}' as Bits.", type_name);
-        bsv_lookup.insert(type_name.to_string(), TypeCategory::Bits);
-        return Ok(());
+/// The payload field of a `Maybe#(T)` that is `width` bits wide: everything below the valid bit.
+pub fn maybe_payload_segment(type_name: &str, width: usize) -> Option<TypeSegment> {
+    let payload_type = maybe_payload_type(type_name)?;
+    if width < 2 {
+        return None;
     }
-    if let Ok(raw_segments) = serde_json::from_value::<Vec<RawSegment>>(raw_value_ref.clone()) {
-        if !raw_segments.is_empty() {
-            let segments = process_nested_segments(raw_segments)?;
-            let total_width = segments.iter().map(|s| s.msb).max().unwrap_or(0).saturating_add(1);
+    Some(TypeSegment {
+        name: Some("Valid".to_string()),
+        msb: width - 2,
+        lsb: 0,
+        type_name: payload_type.to_string(),
+        nested_structure: None,
+    })
+}
 
-            if segments.is_empty() {
-                return Err(format!("No valid segments for typedef {}", type_name).into());
+/// Lays out named fields like BSV `pack` does for a struct: the first field in the MSBs.
+fn build_packed_structure(fields: &[(String, &TypeExpr)], bsv_typedefs: &HashMap<String, TypeStructure>) -> Option<TypeStructure> {
+    let widths: Vec<usize> = fields.iter().map(|(_, t)| t.width(bsv_typedefs)).collect::<Option<_>>()?;
+    if widths.contains(&0) {
+        return None;
+    }
+    let total_width: usize = widths.iter().sum();
+
+    let mut next_msb = total_width;
+    let segments = fields.iter().zip(widths).map(|((name, field_type), width)| {
+        let segment = TypeSegment {
+            name: Some(name.clone()),
+            msb: next_msb - 1,
+            lsb: next_msb - width,
+            type_name: field_type.to_string(),
+            nested_structure: None,
+        };
+        next_msb -= width;
+        segment
+    }).collect();
+
+    Some(build_structure(segments, total_width))
+}
+
+/// Lays out a tuple like BSV `pack`: `tpl_1` in the MSBs down to `tpl_N` in the LSBs.
+fn build_tuple_structure(elements: &[TypeExpr], bsv_typedefs: &HashMap<String, TypeStructure>) -> Option<TypeStructure> {
+    let fields: Vec<(String, &TypeExpr)> = elements.iter().enumerate()
+        .map(|(i, element)| (format!("tpl_{}", i + 1), element))
+        .collect();
+    build_packed_structure(&fields, bsv_typedefs)
+}
+
+/// Lays out a vector like BSV `pack`: element `[n-1]` in the MSBs down to `[0]` in the LSBs.
+fn build_vector_structure(length: usize, element: &TypeExpr, bsv_typedefs: &HashMap<String, TypeStructure>) -> Option<TypeStructure> {
+    let width = element.width(bsv_typedefs).filter(|w| *w > 0)?;
+    let segments = (0..length).map(|i| TypeSegment {
+        name: Some(format!("[{}]", i)),
+        msb: (i + 1) * width - 1,
+        lsb: i * width,
+        type_name: element.to_string(),
+        nested_structure: None,
+    }).collect();
+    Some(build_structure(segments, length * width))
+}
+
+/// Lays out an `Either#(a, b)` as the tagged union `Left a | Right b`.
+fn build_either_structure(type_name: &str, left: &TypeExpr, right: &TypeExpr, bsv_typedefs: &HashMap<String, TypeStructure>) -> Option<TypeStructure> {
+    let arm = |name: &str, tag: u64, payload: &TypeExpr| -> Option<RawUnionMember> {
+        Some(RawUnionMember {
+            name: name.to_string(),
+            tag,
+            type_name: payload.to_string(),
+            width: payload.width(bsv_typedefs)?,
+        })
+    };
+    let members = vec![arm("Left", 0, left)?, arm("Right", 1, right)?];
+    Some(build_union_structure(type_name, None, members))
+}
+
+/// A number (`Int#(n)`, `UInt#(n)`, `FixedPoint#(i, f)`, `Float`...): one leaf of its own
+/// type spanning the whole width.
+fn build_numeric_structure(type_name: &str, total_width: usize) -> TypeStructure {
+    TypeStructure {
+        total_width,
+        segments: vec![TypeSegment {
+            name: None,
+            msb: total_width - 1,
+            lsb: 0,
+            type_name: type_name.to_string(),
+            nested_structure: None,
+        }],
+        enum_definition: None,
+        union_definition: None,
+        vector_definition: None,
+    }
+}
+
+/// Adds a typedef for a type that bluetcl only gave us by name (tuples, vectors, `Either`,
+/// and the built-in numbers and `Bool` when no typedef names them), along with any such
+/// types nested inside it.
+fn resolve_type_name(type_name: &str, bsv_typedefs: &mut HashMap<String, TypeStructure>, bsv_lookup: &mut HashMap<String, TypeCategory>) {
+    if bsv_typedefs.contains_key(type_name) {
+        return;
+    }
+    let Some(expr) = TypeExpr::parse(type_name) else { return };
+
+    let (structure, category) = if let Some(category @ (TypeCategory::Int | TypeCategory::UInt | TypeCategory::FixedPoint | TypeCategory::Float)) = category_from_type_name(type_name) {
+        let structure = expr.width(bsv_typedefs).filter(|w| *w > 0).map(|w| build_numeric_structure(type_name, w));
+        (structure, category)
+    } else if type_name == "Bool" {
+        // Decoded from its one bit, no layout needed
+        bsv_lookup.entry(type_name.to_string()).or_insert(TypeCategory::Bool);
+        return;
+    } else if let Some(elements) = expr.tuple_elements() {
+        for element in elements {
+            resolve_type_name(&element.to_string(), bsv_typedefs, bsv_lookup);
+        }
+        (build_tuple_structure(elements, bsv_typedefs), TypeCategory::Struct)
+    } else if let Some((length, element)) = expr.vector_parts() {
+        resolve_type_name(&element.to_string(), bsv_typedefs, bsv_lookup);
+        (build_vector_structure(length, element, bsv_typedefs), TypeCategory::Vector)
+    } else if let Some(part) = expr.complex_part() {
+        // Complex#(t) is `struct { t rel; t img; }`
+        resolve_type_name(&part.to_string(), bsv_typedefs, bsv_lookup);
+        let fields = [("rel".to_string(), part), ("img".to_string(), part)];
+        (build_packed_structure(&fields, bsv_typedefs), TypeCategory::Struct)
+    } else if let Some((left, right)) = expr.either_parts() {
+        resolve_type_name(&left.to_string(), bsv_typedefs, bsv_lookup);
+        resolve_type_name(&right.to_string(), bsv_typedefs, bsv_lookup);
+        (build_either_structure(type_name, left, right, bsv_typedefs), TypeCategory::Union)
+    } else {
+        return;
+    };
+
+    if let Some(structure) = structure {
+        debug!("INGEST: Laid out '{}' from its type string", type_name);
+        register_field_types(&structure.segments, bsv_lookup);
+        if let Some(union_def) = &structure.union_definition {
+            let payloads: Vec<TypeSegment> = union_def.members.values().filter_map(|m| m.payload.clone()).collect();
+            register_field_types(&payloads, bsv_lookup);
+        }
+        bsv_typedefs.insert(type_name.to_string(), structure);
+        bsv_lookup.insert(type_name.to_string(), category);
+    }
+}
+
+/// Turns a field type as bluetcl reports it into one we can decode: polymorphic
+/// variables are fixed from the field width (`Bit#(numeric type a)` -> `Bit#(8)`) and
+/// unqualified names pick up their package (`Foo_st` -> `test1::Foo_st`).
+fn resolve_field_type(type_name: &str, width: usize, bsv_typedefs: &HashMap<String, TypeStructure>) -> Option<String> {
+    if bsv_typedefs.contains_key(type_name) {
+        return None;
+    }
+    let expr = TypeExpr::parse(type_name)?;
+    if !expr.is_concrete() {
+        return expr.instantiate(width, bsv_typedefs).map(|e| e.to_string());
+    }
+    if let TypeExpr::Constructor { package: None, name, args } = &expr
+        && args.is_empty()
+    {
+        // `pkg::Name` is the type itself; `mkA::pkg::Name` only one module's layout of it
+        let suffix = format!("::{}", name);
+        let candidates: Vec<&String> = bsv_typedefs.keys().filter(|k| k.ends_with(&suffix)).collect();
+        let in_package: Vec<&String> = candidates.iter().copied()
+            .filter(|k| !k[..k.len() - suffix.len()].contains("::"))
+            .collect();
+        for candidates in [in_package, candidates] {
+            if let [qualified] = candidates.as_slice() {
+                return Some((*qualified).clone());
             }
+        }
+    }
+    None
+}
 
-            // NOTE: The TypeStructure constructor here should be updated to include
-            // `enum_definition: None` since this is a Struct/Compound type.
-            bsv_typedefs.insert(type_name.to_string(), TypeStructure {
-                total_width,
-                segments,
-                enum_definition: None // Assumes the new field is present
-            });
+/// Rewrites every field of every typedef through `resolve_field_type`.
+fn instantiate_field_types(bsv_typedefs: &mut HashMap<String, TypeStructure>, bsv_lookup: &mut HashMap<String, TypeCategory>) {
+    fn visit(segments: &mut [TypeSegment], bsv_typedefs: &HashMap<String, TypeStructure>, resolved: &mut Vec<String>) {
+        for segment in segments {
+            if let Some(nested) = segment.nested_structure.as_mut() {
+                visit(&mut nested.segments, bsv_typedefs, resolved);
+                continue;
+            }
+            let width = segment.msb + 1 - segment.lsb;
+            if let Some(concrete) = resolve_field_type(&segment.type_name, width, bsv_typedefs) {
+                debug!("INGEST: Field '{:?}' of type '{}' resolved to '{}'", segment.name, segment.type_name, concrete);
+                segment.type_name = concrete.clone();
+                resolved.push(concrete);
+            }
+        }
+    }
 
-            bsv_lookup.insert(type_name.to_string(), TypeCategory::Struct);
-            return Ok(());
+    let mut resolved = Vec::new();
+    let type_names: Vec<String> = bsv_typedefs.keys().cloned().collect();
+    for type_name in type_names {
+        let Some(mut structure) = bsv_typedefs.get(&type_name).cloned() else { continue };
+        visit(&mut structure.segments, bsv_typedefs, &mut resolved);
+        if let Some(union_def) = structure.union_definition.as_mut() {
+            for member in union_def.members.values_mut() {
+                if let Some(payload) = member.payload.as_mut() {
+                    visit(std::slice::from_mut(payload), bsv_typedefs, &mut resolved);
+                }
+            }
         }
+        bsv_typedefs.insert(type_name, structure);
     }
 
+    for type_name in resolved {
+        if let Some(category) = category_from_type_name(&type_name) {
+            bsv_lookup.entry(type_name.clone()).or_insert(category);
+        }
+        resolve_type_name(&type_name, bsv_typedefs, bsv_lookup);
+    }
+}
 
-    // Attempt to parse as Enum Members
-    if let Ok(raw_members) = serde_json::from_value::<Vec<RawEnumMember>>(raw_value_ref.clone()) {
-        if !raw_members.is_empty() {
-            let max_val = raw_members.iter().map(|m| m.tag).max().unwrap_or(0);
-            let total_width = if max_val > 0 {
-                (max_val as f64).log2().ceil() as usize
-            } else {
-                1 // Minimum width is 1 bit
-            };
+/// Sets each enum's width to the width its fields have in the structs that use it, which
+/// is what bsc actually allocates (`deriving Bits`, custom `pack`, explicit tag values).
+fn fix_enum_widths(bsv_typedefs: &mut HashMap<String, TypeStructure>) {
+    fn collect(segments: &[TypeSegment], widths: &mut HashMap<String, Vec<usize>>) {
+        for segment in segments {
+            match &segment.nested_structure {
+                Some(nested) => collect(&nested.segments, widths),
+                None => widths.entry(segment.type_name.clone()).or_default().push(segment.msb + 1 - segment.lsb),
+            }
+        }
+    }
 
-            let mut enum_members = HashMap::new();
-            for member in raw_members {
-                enum_members.insert(member.tag, member.name);
+    let mut widths: HashMap<String, Vec<usize>> = HashMap::new();
+    for structure in bsv_typedefs.values().filter(|s| s.enum_definition.is_none()) {
+        collect(&structure.segments, &mut widths);
+        if let Some(union_def) = &structure.union_definition {
+            collect(&union_def.members.values().filter_map(|m| m.payload.clone()).collect::<Vec<_>>(), &mut widths);
+        }
+    }
+
+    for (type_name, structure) in bsv_typedefs.iter_mut().filter(|(_, s)| s.enum_definition.is_some()) {
+        let Some(seen) = widths.get(type_name) else { continue };
+        let width = seen.iter().copied().max().unwrap_or(structure.total_width);
+        if seen.iter().any(|w| *w != width) {
+            debug!("INGEST: Enum '{}' used with differing widths {:?}, taking {}", type_name, seen, width);
+        }
+        if width < structure.total_width {
+            debug!("INGEST: Enum '{}' field width {} is too narrow for its tags, keeping {}", type_name, width, structure.total_width);
+            continue;
+        }
+        structure.total_width = width;
+        for segment in structure.segments.iter_mut() {
+            segment.msb = width - 1;
+        }
+    }
+}
+
+/// Switches the listed enums to one-hot decoding.
+fn mark_one_hot_enums(one_hot_enums: &[String], bsv_typedefs: &mut HashMap<String, TypeStructure>) {
+    for type_name in one_hot_enums {
+        match bsv_typedefs.get_mut(type_name).and_then(|s| s.enum_definition.as_mut()) {
+            Some(enum_def) => enum_def.one_hot = true,
+            None => debug!("INGEST: one_hot_enums names '{}', which is not an enum", type_name),
+        }
+    }
+}
+
+/// Sets how the members of an enum, or the tags of a tagged union, are drawn.
+fn apply_enum_kinds(enum_kinds: &HashMap<String, HashMap<String, String>>, bsv_typedefs: &mut HashMap<String, TypeStructure>) {
+    for (type_name, rules) in enum_kinds {
+        let structure = bsv_typedefs.get_mut(type_name);
+        let (member_names, member_kinds): (Vec<&String>, &mut HashMap<String, ValueKind>) = match structure {
+            Some(TypeStructure { enum_definition: Some(enum_def), .. }) =>
+                (enum_def.members.values().collect(), &mut enum_def.member_kinds),
+            Some(TypeStructure { union_definition: Some(union_def), .. }) =>
+                (union_def.members.values().map(|m| &m.name).collect(), &mut union_def.member_kinds),
+            _ => {
+                debug!("INGEST: enum_kinds names '{}', which is not an enum or union", type_name);
+                continue;
+            }
+        };
+        for (member, kind) in rules {
+            if !member_names.contains(&member) {
+                debug!("INGEST: enum_kinds names '{}', which is not a member of '{}'", member, type_name);
+            }
+            match parse_value_kind(kind) {
+                Some(kind) => { member_kinds.insert(member.clone(), kind); }
+                None => debug!("INGEST: Unknown kind '{}' for '{}::{}'", kind, type_name, member),
             }
+        }
+    }
+}
 
-            // 🌟 FIX: Create the single segment for the enum
-            let segment = TypeSegment {
-                name: None,
-                msb: total_width.saturating_sub(1), // Correct MSB (width - 1)
-                lsb: 0,
-                // 🌟 FIX: Store the canonical type name here.
-                type_name: type_name.to_string(),
-                nested_structure: None
-            };
+/// Marks `Char` and the configured byte-vector types as text.
+fn mark_text_types(text_types: &[String], bsv_typedefs: &mut HashMap<String, TypeStructure>, bsv_lookup: &mut HashMap<String, TypeCategory>) {
+    bsv_typedefs.entry("Char".to_string()).or_insert_with(|| TypeStructure {
+        total_width: 8,
+        segments: vec![TypeSegment {
+            name: None,
+            msb: 7,
+            lsb: 0,
+            type_name: "Char".to_string(),
+            nested_structure: None,
+        }],
+        enum_definition: None,
+        union_definition: None,
+        vector_definition: None,
+    });
+    bsv_lookup.insert("Char".to_string(), TypeCategory::Text);
 
-            // 🌟 FIX: Store the TypeStructure with the enum definition members correctly
-            // placed in the dedicated `enum_definition` field.
-            bsv_typedefs.insert(type_name.to_string(), TypeStructure {
-                total_width,
-                segments: vec![segment],
-                enum_definition: Some(crate::helper::EnumDefinition { members: enum_members }), // Use new field
-            });
+    for type_name in text_types {
+        match bsv_typedefs.get(type_name) {
+            Some(structure) if structure.total_width.is_multiple_of(8) => {
+                bsv_lookup.insert(type_name.clone(), TypeCategory::Text);
+            }
+            _ => debug!("INGEST: text_types names '{}', which is not a whole number of bytes", type_name),
+        }
+    }
+}
 
-            bsv_lookup.insert(type_name.to_string(), TypeCategory::Enum);
-            return Ok(());
+/// Every type named by a block port or a typedef field.
+fn referenced_type_names(bsv_modules: &HashMap<String, ModuleData>, bsv_typedefs: &HashMap<String, TypeStructure>) -> Vec<String> {
+    fn collect(segments: &[TypeSegment], names: &mut Vec<String>) {
+        for segment in segments {
+            match &segment.nested_structure {
+                Some(nested) => collect(&nested.segments, names),
+                None => names.push(segment.type_name.clone()),
+            }
         }
     }
 
-    // Default or empty definition is Bits/other simple type
-    if type_name.starts_with("Bit#") || type_name == "Bool" || raw_value_ref.is_array() && raw_value_ref.as_array().unwrap().is_empty() {
-        bsv_lookup.insert(type_name.to_string(), TypeCategory::Bits);
+    let mut names: Vec<String> = bsv_modules.values()
+        .flat_map(|m| m.blocks.values())
+        .flat_map(|b| b.ports.iter().map(|p| p.type_name.clone()))
+        .collect();
+    for def in bsv_typedefs.values() {
+        collect(&def.segments, &mut names);
+        if let Some(union_def) = &def.union_definition {
+            collect(&union_def.members.values().filter_map(|m| m.payload.clone()).collect::<Vec<_>>(), &mut names);
+        }
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Categories that follow from the type name alone, whatever bluetcl reported as the layout.
+pub fn category_from_type_name(type_name: &str) -> Option<TypeCategory> {
+    if type_name == "Bool" {
+        Some(TypeCategory::Bool)
+    } else if maybe_payload_type(type_name).is_some() {
+        Some(TypeCategory::Maybe)
+    } else if type_name.starts_with("Bit#(") {
+        Some(TypeCategory::Bits)
+    } else if type_name.starts_with("Int#(") {
+        Some(TypeCategory::Int)
+    } else if type_name.starts_with("UInt#(") {
+        Some(TypeCategory::UInt)
     } else {
-        // Fallback for types that weren't parsed but exist
+        let expr = TypeExpr::parse(type_name)?;
+        if expr.fixed_point_parts().is_some() {
+            Some(TypeCategory::FixedPoint)
+        } else if expr.float_parts().is_some() {
+            Some(TypeCategory::Float)
+        } else {
+            None
+        }
+    }
+}
+
+/// Registers field types that are recognised from their name alone (e.g. `Maybe#(type a)`).
+fn register_field_types(segments: &[TypeSegment], bsv_lookup: &mut HashMap<String, TypeCategory>) {
+    for segment in segments {
+        if let Some(nested) = &segment.nested_structure {
+            register_field_types(&nested.segments, bsv_lookup);
+        } else if let Some(category) = category_from_type_name(&segment.type_name) {
+            bsv_lookup.entry(segment.type_name.clone()).or_insert(category);
+        }
+    }
+}
+
+/// An enum: a single segment of its own type spanning the whole width, with the members
+/// in the dedicated `enum_definition` field.
+fn build_enum_structure(type_name: &str, total_width: usize, members: HashMap<u64, String>) -> TypeStructure {
+    let segment = TypeSegment {
+        name: None,
+        msb: total_width.saturating_sub(1),
+        lsb: 0,
+        type_name: type_name.to_string(),
+        nested_structure: None
+    };
+    TypeStructure {
+        total_width,
+        segments: vec![segment],
+        enum_definition: Some(crate::helper::EnumDefinition { members, one_hot: false, member_kinds: HashMap::new() }),
+        union_definition: None,
+        vector_definition: None,
+    }
+}
+
+fn process_typedef(type_name: &str, raw_typedef: RawTypedef, bsv_typedefs: &mut HashMap<String, TypeStructure>, bsv_lookup: &mut HashMap<String, TypeCategory>) -> Result<(), Box<dyn std::error::Error>> {
+
+    if type_name == "Bool" {
+        bsv_lookup.insert(type_name.to_string(), TypeCategory::Bool);
+        return Ok(());
+    }
+    if type_name.starts_with("Bit#(") || type_name == "Clock" || type_name == "Reset" {
+        debug!("INGEST: Explicitly marking primitive type '{}' as Bits.", type_name);
         bsv_lookup.insert(type_name.to_string(), TypeCategory::Bits);
+        return Ok(());
+    }
+    // Either#(a, b) and Complex#(t) are always laid out from their type string once all
+    // typedefs are known: bluetcl reports their fields with the parameters unresolved
+    if TypeExpr::parse(type_name).is_some_and(|e| e.either_parts().is_some() || e.complex_part().is_some()) {
+        debug!("INGEST: Deferring '{}' until its arm widths are known", type_name);
+        return Ok(());
+    }
+    // Tuples are structs with fields tpl_1..tpl_N. Anything else bluetcl gave us is
+    // ignored here and laid out from the type string once all typedefs are known.
+    if TypeExpr::parse(type_name).is_some_and(|e| e.tuple_elements().is_some()) {
+        let has_fields = matches!(&raw_typedef, RawTypedef::Struct { fields, .. }
+            if !fields.is_empty() && fields.iter().all(|s| s.var_name.as_deref().is_some_and(|n| n.starts_with("tpl_"))));
+        if !has_fields {
+            debug!("INGEST: Deferring tuple '{}' until its element widths are known", type_name);
+            return Ok(());
+        }
+    }
+    // Maybe#(T): valid bit in the MSB, T below it. Only the width is taken from the JSON,
+    // so both raw bluetcl dumps and older post-processed Valid/Value segments work.
+    if let Some(payload_type) = maybe_payload_type(type_name) {
+        let total_width = raw_typedef.width()
+            .ok_or_else(|| format!("No width for typedef {}", type_name))?;
+        let valid = TypeSegment {
+            name: Some("Valid".to_string()),
+            msb: total_width - 1,
+            lsb: total_width - 1,
+            type_name: "Bool".to_string(),
+            nested_structure: None,
+        };
+        let mut segments = vec![valid];
+        segments.extend(maybe_payload_segment(type_name, total_width));
+        debug!("INGEST: Maybe '{}' carries '{}' in {} bits", type_name, payload_type, total_width);
+        bsv_typedefs.insert(type_name.to_string(), TypeStructure {
+            total_width,
+            segments,
+            enum_definition: None,
+            union_definition: None,
+            vector_definition: None,
+        });
+        bsv_lookup.insert(type_name.to_string(), TypeCategory::Maybe);
+        return Ok(());
+    }
+    // Int#(n), UInt#(n), FixedPoint#(i, f), Float...: a single numeric leaf spanning the whole
+    // width. bluetcl reports the library structs behind these too, but we decode them as numbers.
+    if let Some(category @ (TypeCategory::Int | TypeCategory::UInt | TypeCategory::FixedPoint | TypeCategory::Float)) = category_from_type_name(type_name) {
+        let total_width = raw_typedef.width()
+            .or_else(|| TypeExpr::parse(type_name)?.width(bsv_typedefs))
+            .filter(|w| *w > 0)
+            .ok_or_else(|| format!("No width for typedef {}", type_name))?;
+        bsv_typedefs.insert(type_name.to_string(), build_numeric_structure(type_name, total_width));
+        bsv_lookup.insert(type_name.to_string(), category);
+        return Ok(());
+    }
+    match raw_typedef {
+        RawTypedef::Struct { width, fields } if !fields.is_empty() => {
+            let segments = process_nested_segments(fields)?;
+            register_field_types(&segments, bsv_lookup);
+            // Padding above the highest field only shows in the declared width
+            let total_width = width.unwrap_or_else(|| segments.iter().map(|s| s.msb).max().unwrap_or(0).saturating_add(1));
+
+            if segments.is_empty() {
+                return Err(format!("No valid segments for typedef {}", type_name).into());
+            }
+
+            let structure = build_structure(segments, total_width);
+            let category = if structure.vector_definition.is_some() { TypeCategory::Vector } else { TypeCategory::Struct };
+            bsv_typedefs.insert(type_name.to_string(), structure);
+
+            bsv_lookup.insert(type_name.to_string(), category);
+        }
+        RawTypedef::Union { width, members } if !members.is_empty() => {
+            let structure = build_union_structure(type_name, width, members);
+            bsv_typedefs.insert(type_name.to_string(), structure);
+            bsv_lookup.insert(type_name.to_string(), TypeCategory::Union);
+        }
+        RawTypedef::Enum { width, members } if !members.is_empty() => {
+            // Narrowest width that holds every tag unless the file gives one; `fix_enum_widths`
+            // widens it to the width the enum actually occupies in structs once all typedefs are read.
+            let max_val = members.iter().map(|m| m.tag).max().unwrap_or(0);
+            let total_width = width.unwrap_or(((u64::BITS - max_val.leading_zeros()) as usize).max(1));
+
+            let mut enum_members = HashMap::new();
+            for member in members {
+                enum_members.insert(member.tag, member.name);
+            }
+
+            bsv_typedefs.insert(type_name.to_string(), build_enum_structure(type_name, total_width, enum_members));
+            bsv_lookup.insert(type_name.to_string(), TypeCategory::Enum);
+        }
+        RawTypedef::Alias { target } => {
+            if !add_alias(type_name, &target, bsv_typedefs, bsv_lookup) {
+                return Err(format!("Cannot lay out '{}', the type '{}' stands for", type_name, target).into());
+            }
+        }
+        RawTypedef::Vector { length, element } => {
+            resolve_type_name(&element, bsv_typedefs, bsv_lookup);
+            let structure = TypeExpr::parse(&element)
+                .and_then(|element| build_vector_structure(length, &element, bsv_typedefs))
+                .ok_or_else(|| format!("Cannot lay out '{}', no width for its element type '{}'", type_name, element))?;
+            register_field_types(&structure.segments, bsv_lookup);
+            bsv_typedefs.insert(type_name.to_string(), structure);
+            bsv_lookup.insert(type_name.to_string(), TypeCategory::Vector);
+        }
+        // Empty definitions (Clock, Reset, ...) are Bits/other simple types
+        _ => {
+            bsv_lookup.insert(type_name.to_string(), TypeCategory::Bits);
+        }
     }
 
     Ok(())
 }
 
+/// Lays out a BSV tagged union: the tag occupies the MSBs and every arm's payload
+/// is left-aligned directly below it, so narrower arms leave padding in the LSBs.
+/// Lays out a tagged union. The tag takes every bit of the declared width above the widest
+/// payload, or else just enough bits for the largest tag.
+fn build_union_structure(type_name: &str, width: Option<usize>, raw_members: Vec<RawUnionMember>) -> TypeStructure {
+    let max_tag = raw_members.iter().map(|m| m.tag).max().unwrap_or(0);
+    let payload_width = raw_members.iter().map(|m| m.width).max().unwrap_or(0);
+    let tag_width = (u64::BITS - max_tag.leading_zeros()) as usize;
+    let tag_width = width.filter(|w| *w >= tag_width + payload_width).map_or(tag_width, |w| w - payload_width);
+    let total_width = tag_width + payload_width;
+
+    let mut members = HashMap::new();
+    for member in raw_members {
+        let payload = if member.width == 0 || member.type_name == "void" {
+            None
+        } else {
+            Some(TypeSegment {
+                name: Some(member.name.clone()),
+                msb: payload_width - 1,
+                lsb: payload_width - member.width,
+                type_name: member.type_name,
+                nested_structure: None,
+            })
+        };
+        members.insert(member.tag, UnionMember { name: member.name, payload });
+    }
+
+    let mut segments = Vec::new();
+    if tag_width > 0 {
+        segments.push(TypeSegment {
+            name: Some("tag".to_string()),
+            msb: total_width - 1,
+            lsb: payload_width,
+            type_name: type_name.to_string(),
+            nested_structure: None,
+        });
+    }
+
+    TypeStructure {
+        total_width,
+        segments,
+        enum_definition: None,
+        union_definition: Some(UnionDefinition { tag_width, members, member_kinds: HashMap::new() }),
+        vector_definition: None,
+    }
+}
+
 fn process_module_blocks(
     raw_blocks: HashMap<String, ModuleBlockJson> 
 ) -> Result<HashMap<String, RawBlockDefinition>, Box<dyn std::error::Error>> {
@@ -279,32 +810,703 @@ fn process_module_blocks(
     Ok(block_defs_map)
 }
 
+// One method of a synthesized submodule, gathered from the instance's ports
+#[derive(Debug, Default)]
+struct MethodPorts {
+    name: String,
+    enable: Option<RawBlockPort>,
+    ready: Option<RawBlockPort>,
+    arguments: Vec<(String, RawBlockPort)>,
+    result: Option<RawBlockPort>,
+}
+
+/// Groups the ports of a submodule instance into its methods, as bsc names them:
+/// `EN_m` and `RDY_m` are the handshake, `m_arg` the arguments and `m` the result.
+/// Clocks and resets belong to no method.
+fn group_method_ports(ports: &[RawBlockPort]) -> Vec<MethodPorts> {
+    let mut methods: Vec<MethodPorts> = Vec::new();
+    fn method<'a>(methods: &'a mut Vec<MethodPorts>, name: &str) -> &'a mut MethodPorts {
+        if let Some(i) = methods.iter().position(|m| m.name == name) {
+            return &mut methods[i];
+        }
+        methods.push(MethodPorts { name: name.to_string(), ..Default::default() });
+        methods.last_mut().unwrap()
+    }
+
+    let ports: Vec<&RawBlockPort> = ports.iter().filter(|p| p.type_name != "Clock" && p.type_name != "Reset").collect();
+    for port in &ports {
+        if let Some(name) = port.name.strip_prefix("EN_") {
+            method(&mut methods, name).enable = Some((*port).clone());
+        } else if let Some(name) = port.name.strip_prefix("RDY_") {
+            method(&mut methods, name).ready = Some((*port).clone());
+        }
+    }
+    for port in ports.into_iter().filter(|p| !p.name.starts_with("EN_") && !p.name.starts_with("RDY_")) {
+        let owner = methods.iter()
+            .filter(|m| port.name.strip_prefix(m.name.as_str()).is_some_and(|rest| rest.starts_with('_')))
+            .max_by_key(|m| m.name.len())
+            .map(|m| m.name.clone());
+        match owner {
+            Some(owner) => {
+                let argument = port.name[owner.len() + 1..].to_string();
+                method(&mut methods, &owner).arguments.push((argument, port.clone()));
+            }
+            // A value method without a RDY_ port (always_ready) is just its result
+            None => method(&mut methods, &port.name).result = Some(port.clone()),
+        }
+    }
+    methods
+}
+
+/// Lays out a submodule's methods as one compound, the first method in the MSBs. Each
+/// method is `{EN, RDY, arguments..., result}`, leaving out the ports it doesn't have.
+fn build_interface_structure(methods: &[MethodPorts], bsv_typedefs: &HashMap<String, TypeStructure>) -> Option<TypeStructure> {
+    let mut method_structures = Vec::new();
+    for method in methods {
+        let mut fields: Vec<(String, TypeExpr)> = Vec::new();
+        for (name, port) in [("EN", &method.enable), ("RDY", &method.ready)] {
+            if let Some(port) = port {
+                fields.push((name.to_string(), TypeExpr::parse(&port.type_name)?));
+            }
+        }
+        for (name, port) in &method.arguments {
+            fields.push((name.clone(), TypeExpr::parse(&port.type_name)?));
+        }
+        if let Some(port) = &method.result {
+            fields.push(("result".to_string(), TypeExpr::parse(&port.type_name)?));
+        }
+        let fields: Vec<(String, &TypeExpr)> = fields.iter().map(|(name, t)| (name.clone(), t)).collect();
+        method_structures.push((method.name.clone(), build_packed_structure(&fields, bsv_typedefs)?));
+    }
+
+    let total_width: usize = method_structures.iter().map(|(_, s)| s.total_width).sum();
+    let mut next_msb = total_width;
+    let segments = method_structures.into_iter().map(|(name, structure)| {
+        let segment = TypeSegment {
+            name: Some(name),
+            msb: next_msb - 1,
+            lsb: next_msb - structure.total_width,
+            type_name: "Compound".to_string(),
+            nested_structure: Some(Box::new(structure)),
+        };
+        next_msb -= segment.msb + 1 - segment.lsb;
+        segment
+    }).collect();
+    Some(build_structure(segments, total_width))
+}
+
+/// Registers every synthesized submodule type (e.g. `mkA`) as an Interface whose layout
+/// groups the instance's ports into methods.
+fn register_interfaces(bsv_modules: &HashMap<String, ModuleData>, bsv_typedefs: &mut HashMap<String, TypeStructure>, bsv_lookup: &mut HashMap<String, TypeCategory>) {
+    for block in bsv_modules.values().flat_map(|m| m.blocks.values()) {
+        if bsv_typedefs.contains_key(&block.block_type_name) {
+            continue;
+        }
+        // Primitives (RegN, FIFO2, ...) use their own port names; only bsc methods have EN_/RDY_
+        if !block.ports.iter().any(|p| p.name.starts_with("EN_") || p.name.starts_with("RDY_")) {
+            continue;
+        }
+        let methods = group_method_ports(&block.ports);
+        match build_interface_structure(&methods, bsv_typedefs) {
+            Some(structure) => {
+                debug!("INGEST: '{}' has methods {:?}", block.block_type_name, methods.iter().map(|m| &m.name).collect::<Vec<_>>());
+                bsv_typedefs.insert(block.block_type_name.clone(), structure);
+                bsv_lookup.insert(block.block_type_name.clone(), TypeCategory::Interface);
+            }
+            None => debug!("INGEST: Cannot lay out the methods of '{}': a port type has no known width", block.block_type_name),
+        }
+    }
+}
+
+/// Type names a layout refers to: its fields, union payloads and vector elements.
+fn field_type_names(structure: &TypeStructure) -> Vec<&str> {
+    let mut names = Vec::new();
+    for segment in &structure.segments {
+        match &segment.nested_structure {
+            Some(nested) => names.extend(field_type_names(nested)),
+            None => names.push(segment.type_name.as_str()),
+        }
+    }
+    if let Some(union_def) = &structure.union_definition {
+        names.extend(union_def.members.values().filter_map(|m| m.payload.as_ref()).map(|p| p.type_name.as_str()));
+    }
+    if let Some(vector_def) = &structure.vector_definition {
+        names.push(vector_def.element_type.as_str());
+    }
+    names
+}
+
+fn rename_field_types(structure: &mut TypeStructure, rename: &dyn Fn(&str) -> Option<String>) {
+    for segment in structure.segments.iter_mut() {
+        match segment.nested_structure.as_mut() {
+            Some(nested) => rename_field_types(nested, rename),
+            None => if let Some(name) = rename(&segment.type_name) { segment.type_name = name },
+        }
+    }
+    if let Some(union_def) = structure.union_definition.as_mut() {
+        for payload in union_def.members.values_mut().filter_map(|m| m.payload.as_mut()) {
+            if let Some(name) = rename(&payload.type_name) {
+                payload.type_name = name;
+            }
+        }
+    }
+    if let Some(vector_def) = structure.vector_definition.as_mut()
+        && let Some(name) = rename(&vector_def.element_type)
+    {
+        vector_def.element_type = name;
+    }
+}
+
+// A module's name with its typedefs and their categories
+type ModuleTypedefs = (String, HashMap<String, TypeStructure>, HashMap<String, TypeCategory>);
+
+/// Merges the typedefs of every module into one table. When modules disagree on a layout
+/// the top module's wins, then the first module by name; the other layouts are kept as
+/// `<module>::<type>` (e.g. `mkA::test1::Foo_st`), together with every type of that module
+/// that refers to them, and that module's ports and fields are pointed at them.
+fn merge_module_typedefs(
+    top: &str,
+    mut module_typedefs: Vec<ModuleTypedefs>,
+    bsv_modules: &mut HashMap<String, ModuleData>,
+) -> (HashMap<String, TypeStructure>, HashMap<String, TypeCategory>) {
+    module_typedefs.sort_by(|(a, _, _), (b, _, _)| (a != top, a).cmp(&(b != top, b)));
+
+    let mut bsv_typedefs: HashMap<String, TypeStructure> = HashMap::new();
+    let mut bsv_lookup: HashMap<String, TypeCategory> = HashMap::new();
+    for (_, typedefs, lookup) in &module_typedefs {
+        for (type_name, structure) in typedefs {
+            bsv_typedefs.entry(type_name.clone()).or_insert_with(|| structure.clone());
+        }
+        for (type_name, category) in lookup {
+            bsv_lookup.entry(type_name.clone()).or_insert_with(|| category.clone());
+        }
+    }
+
+    for (module_name, typedefs, lookup) in &module_typedefs {
+        let mut scoped: Vec<&String> = typedefs.iter()
+            .filter(|(type_name, structure)| bsv_typedefs.get(*type_name) != Some(*structure))
+            .map(|(type_name, _)| type_name)
+            .collect();
+        if scoped.is_empty() {
+            continue;
+        }
+        for type_name in &scoped {
+            warn!("INGEST: '{}' in {} conflicts with the layout already in use; {} uses '{}::{}'",
+                type_name, module_name, module_name, module_name, type_name);
+        }
+        // Types that refer to a conflicting type have to follow it
+        loop {
+            let followers: Vec<&String> = typedefs.iter()
+                .filter(|(type_name, structure)| !scoped.contains(type_name)
+                    && field_type_names(structure).iter().any(|t| scoped.iter().any(|s| s == t)))
+                .map(|(type_name, _)| type_name)
+                .collect();
+            if followers.is_empty() {
+                break;
+            }
+            scoped.extend(followers);
+        }
+
+        let rename = |type_name: &str| scoped.iter().any(|s| *s == type_name).then(|| format!("{}::{}", module_name, type_name));
+        for type_name in &scoped {
+            let mut structure = typedefs[*type_name].clone();
+            rename_field_types(&mut structure, &rename);
+            let scoped_name = format!("{}::{}", module_name, type_name);
+            if let Some(category) = lookup.get(*type_name) {
+                bsv_lookup.insert(scoped_name.clone(), category.clone());
+            }
+            bsv_typedefs.insert(scoped_name, structure);
+        }
+        if let Some(module_data) = bsv_modules.get_mut(module_name) {
+            for port in module_data.blocks.values_mut().flat_map(|b| b.ports.iter_mut()) {
+                if let Some(scoped_name) = rename(&port.type_name) {
+                    port.type_name = scoped_name;
+                }
+            }
+        }
+    }
+    (bsv_typedefs, bsv_lookup)
+}
+
+/// Checks an override for fields that run backwards, overflow the width or overlap.
+fn validate_override(type_name: &str, total_width: usize, fields: &[OverrideField]) -> Result<(), String> {
+    let mut used = vec![None; total_width];
+    for field in fields {
+        if field.msb < field.lsb || field.msb >= total_width {
+            return Err(format!("field '{}' [{}:{}] does not fit in {} bits", field.name, field.msb, field.lsb, total_width));
+        }
+        for (bit, owner) in used.iter_mut().enumerate().take(field.msb + 1).skip(field.lsb) {
+            if let Some(other) = owner.replace(&field.name) {
+                return Err(format!("fields '{}' and '{}' overlap at bit {}", other, field.name, bit));
+            }
+        }
+    }
+    if fields.is_empty() {
+        return Err(format!("no fields for {}", type_name));
+    }
+    Ok(())
+}
+
+/// Describes how an override differs from the layout bluetcl generated, if it does.
+fn override_conflict(generated: &TypeStructure, replacement: &TypeStructure) -> Option<String> {
+    if generated.enum_definition.is_some() || generated.union_definition.is_some() {
+        return Some("replaces a generated enum/union".to_string());
+    }
+    if generated.total_width != replacement.total_width {
+        return Some(format!("width {} replaces generated width {}", replacement.total_width, generated.total_width));
+    }
+    let layout = |s: &TypeStructure| {
+        let mut fields: Vec<(Option<String>, usize, usize)> = s.segments.iter().map(|f| (f.name.clone(), f.msb, f.lsb)).collect();
+        fields.sort();
+        fields
+    };
+    (layout(generated) != layout(replacement)).then(|| format!("fields {:?} replace generated fields {:?}", layout(replacement), layout(generated)))
+}
+
+/// Reads user layouts from the overrides file and merges them into the typedefs.
+fn apply_overrides(filename: &str, bsv_typedefs: &mut HashMap<String, TypeStructure>, bsv_lookup: &mut HashMap<String, TypeCategory>) {
+    let bytes = read_bsv_file(filename);
+    if bytes.is_empty() {
+        return;
+    }
+    match serde_json::from_slice(&bytes) {
+        Ok(overrides) => merge_overrides(overrides, bsv_typedefs, bsv_lookup),
+        Err(e) => warn!("Error parsing {}: {}", filename, e),
+    }
+}
+
+/// Merges user layouts into the typedefs. Overrides win over generated data; every
+/// conflict is reported.
+fn merge_overrides(overrides: HashMap<String, TypeOverride>, bsv_typedefs: &mut HashMap<String, TypeStructure>, bsv_lookup: &mut HashMap<String, TypeCategory>) {
+    for (type_name, type_override) in overrides {
+        let total_width = type_override.width
+            .unwrap_or_else(|| type_override.fields.iter().map(|f| f.msb + 1).max().unwrap_or(0));
+        if let Err(e) = validate_override(&type_name, total_width, &type_override.fields) {
+            warn!("OVERRIDE: Ignoring '{}': {}", type_name, e);
+            continue;
+        }
+
+        let segments: Vec<TypeSegment> = type_override.fields.into_iter().map(|field| TypeSegment {
+            name: Some(field.name),
+            msb: field.msb,
+            lsb: field.lsb,
+            type_name: field.type_name,
+            nested_structure: None,
+        }).collect();
+        register_field_types(&segments, bsv_lookup);
+        let structure = build_structure(segments, total_width);
+        let category = if structure.vector_definition.is_some() { TypeCategory::Vector } else { TypeCategory::Struct };
+
+        match bsv_typedefs.get(&type_name).and_then(|generated| override_conflict(generated, &structure)) {
+            Some(conflict) => warn!("OVERRIDE: '{}' conflicts with bluespec.json: {}", type_name, conflict),
+            None => debug!("OVERRIDE: Applying layout for '{}'", type_name),
+        }
+        bsv_typedefs.insert(type_name.clone(), structure);
+        bsv_lookup.insert(type_name, category);
+    }
+}
+
+/// Re-tags one enum or union with the constructor numbers bsc used. Unions keep their
+/// payload layout; only the tag above it changes width.
+fn apply_constructor_table(type_name: &str, table: &ConstructorTable, structure: &mut TypeStructure) {
+    if let Some(enum_def) = structure.enum_definition.as_mut() {
+        enum_def.members = table.tags.iter().map(|(name, tag)| (*tag, name.clone())).collect();
+        structure.total_width = table.tag_width;
+        for segment in structure.segments.iter_mut() {
+            segment.msb = table.tag_width.saturating_sub(1);
+        }
+    } else if let Some(union_def) = structure.union_definition.as_mut() {
+        let payload_width = structure.total_width - union_def.tag_width;
+        let mut members = HashMap::new();
+        for member in union_def.members.values() {
+            match table.tags.iter().find(|(name, _)| *name == member.name) {
+                Some((_, tag)) => { members.insert(*tag, member.clone()); }
+                None => debug!("DSYM: No tag for '{}' of '{}', dropping it", member.name, type_name),
+            }
+        }
+        union_def.members = members;
+        union_def.tag_width = table.tag_width;
+        structure.total_width = table.tag_width + payload_width;
+        structure.segments.retain(|s| s.name.as_deref() != Some("tag"));
+        if table.tag_width > 0 {
+            structure.segments.insert(0, TypeSegment {
+                name: Some("tag".to_string()),
+                msb: structure.total_width - 1,
+                lsb: payload_width,
+                type_name: type_name.to_string(),
+                nested_structure: None,
+            });
+        }
+    }
+}
+
+/// A layout that is nothing but one field of its own type spanning the whole width.
+fn is_self_typed(type_name: &str, structure: &TypeStructure) -> bool {
+    matches!(structure.segments.as_slice(), [segment]
+        if segment.type_name == type_name && segment.lsb == 0 && segment.msb + 1 == structure.total_width)
+}
+
+/// Merges the enum and union tags from bsc's optional `-dsyminitial` dump. They win over
+/// bluespec.json, and enums bluetcl left out altogether are added.
+fn apply_dsyminitial(filename: &str, bsv_typedefs: &mut HashMap<String, TypeStructure>, bsv_lookup: &mut HashMap<String, TypeCategory>) {
+    let bytes = read_bsv_file(filename);
+    if bytes.is_empty() {
+        return;
+    }
+    let tables = parse_dsyminitial(&String::from_utf8_lossy(&bytes));
+    debug!("DSYM: {} constructor tables in {}", tables.len(), filename);
+
+    for (type_name, table) in tables {
+        match bsv_typedefs.get_mut(&type_name) {
+            Some(structure) if structure.enum_definition.is_some() || structure.union_definition.is_some() => {
+                apply_constructor_table(&type_name, &table, structure);
+            }
+            // Older list_signals.tcl wrote an enum as one field of the enum's own type
+            Some(structure) if table.is_enum && is_self_typed(&type_name, structure) => {
+                debug!("DSYM: '{}' is an enum", type_name);
+                let members = table.tags.into_iter().map(|(name, tag)| (tag, name)).collect();
+                *structure = build_enum_structure(&type_name, table.tag_width, members);
+                bsv_lookup.insert(type_name, TypeCategory::Enum);
+            }
+            Some(_) => debug!("DSYM: '{}' is not an enum or union in bluespec.json, ignoring its tags", type_name),
+            None if table.is_enum => {
+                let members = table.tags.into_iter().map(|(name, tag)| (tag, name)).collect();
+                bsv_typedefs.insert(type_name.clone(), build_enum_structure(&type_name, table.tag_width, members));
+                bsv_lookup.insert(type_name, TypeCategory::Enum);
+            }
+            // Constructors of types the design never names (Prelude, other packages)
+            None => {}
+        }
+    }
+}
+
+/// Reads the BSV files listed in the sources file (e.g. `["test1.bsv"]`) for their typedefs.
+fn load_bsv_sources(filename: &str) -> SourceTypes {
+    let bytes = read_bsv_file(filename);
+    if bytes.is_empty() {
+        return SourceTypes::default();
+    }
+    let source_files: Vec<String> = match serde_json::from_slice(&bytes) {
+        Ok(source_files) => source_files,
+        Err(e) => {
+            debug!("Error parsing {}: {}", filename, e);
+            return SourceTypes::default();
+        }
+    };
+    let files: Vec<(String, String)> = source_files.into_iter()
+        .filter_map(|file_name| {
+            let bytes = read_bsv_file(&file_name);
+            if bytes.is_empty() {
+                debug!("BSV: Cannot read {}", file_name);
+                return None;
+            }
+            Some((file_name, String::from_utf8_lossy(&bytes).into_owned()))
+        })
+        .collect();
+    parse_bsv_sources(&files)
+}
+
+/// Adds the typedefs read from BSV sources that bluespec.json doesn't have. An alias gets
+/// the layout and category of the type it stands for.
+fn add_source_types(source_types: SourceTypes, bsv_typedefs: &mut HashMap<String, TypeStructure>, bsv_lookup: &mut HashMap<String, TypeCategory>) {
+    for (type_name, raw_typedef) in source_types.typedefs {
+        if bsv_typedefs.contains_key(&type_name) {
+            continue;
+        }
+        debug!("BSV: Taking '{}' from the sources", type_name);
+        if let Err(e) = process_typedef(&type_name, raw_typedef, bsv_typedefs, bsv_lookup) {
+            debug!("Error processing typedef '{}': {}", type_name, e);
+        }
+    }
+}
+
+/// Gives an alias the layout and category of the type it stands for. False when that
+/// type cannot be laid out (yet).
+fn add_alias(alias: &str, target: &str, bsv_typedefs: &mut HashMap<String, TypeStructure>, bsv_lookup: &mut HashMap<String, TypeCategory>) -> bool {
+    resolve_type_name(target, bsv_typedefs, bsv_lookup);
+    let category = bsv_lookup.get(target).cloned().or_else(|| category_from_type_name(target));
+    let structure = bsv_typedefs.get(target).cloned().or_else(|| {
+        let width = TypeExpr::parse(target)?.width(bsv_typedefs)?;
+        Some(TypeStructure { total_width: width, segments: vec![], enum_definition: None, union_definition: None, vector_definition: None })
+    });
+    match (category, structure) {
+        (Some(category), Some(structure)) => {
+            bsv_typedefs.insert(alias.to_string(), structure);
+            bsv_lookup.insert(alias.to_string(), category);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Lays out one module's typedefs. Aliases and vectors go once the types they name are
+/// known, which may take several rounds when they name each other.
+fn process_module_typedefs(raw_typedefs: HashMap<String, RawTypedef>) -> (HashMap<String, TypeStructure>, HashMap<String, TypeCategory>) {
+    let mut typedefs = HashMap::new();
+    let mut lookup = HashMap::new();
+    let (mut pending, layouts): (Vec<_>, Vec<_>) = raw_typedefs.into_iter().partition(|(_, t)| t.names_other_types());
+    for (type_name, raw_typedef) in layouts {
+        if let Err(e) = process_typedef(&type_name, raw_typedef, &mut typedefs, &mut lookup) {
+            debug!("Error processing typedef '{}': {}", type_name, e);
+        }
+    }
+    while !pending.is_empty() {
+        let mut failed = Vec::new();
+        for (type_name, raw_typedef) in pending.iter() {
+            if let Err(e) = process_typedef(type_name, raw_typedef.clone(), &mut typedefs, &mut lookup) {
+                failed.push((type_name.clone(), raw_typedef.clone(), e.to_string()));
+            }
+        }
+        if failed.len() == pending.len() {
+            for (type_name, _, e) in failed {
+                debug!("Error processing typedef '{}': {}", type_name, e);
+            }
+            break;
+        }
+        pending = failed.into_iter().map(|(type_name, raw_typedef, _)| (type_name, raw_typedef)).collect();
+    }
+    (typedefs, lookup)
+}
+
+/// Reads the bsc-generated Verilog files listed in the Verilog file (e.g. `["mkTop.v"]`).
+fn load_verilog(filename: &str) -> Vec<VerilogModule> {
+    let bytes = read_bsv_file(filename);
+    if bytes.is_empty() {
+        return vec![];
+    }
+    let verilog_files: Vec<String> = match serde_json::from_slice(&bytes) {
+        Ok(verilog_files) => verilog_files,
+        Err(e) => {
+            warn!("Error parsing {}: {}", filename, e);
+            return vec![];
+        }
+    };
+    verilog_files.into_iter()
+        .flat_map(|file_name| {
+            let bytes = read_bsv_file(&file_name);
+            if bytes.is_empty() {
+                warn!("Verilog: Cannot read {}", file_name);
+            }
+            parse_verilog(&String::from_utf8_lossy(&bytes))
+        })
+        .collect()
+}
+
+/// Port type for a Verilog port: the width is all the Verilog knows.
+fn verilog_port_type(port: &str, width: usize) -> String {
+    if port.starts_with("CLK") {
+        "Clock".to_string()
+    } else if port.starts_with("RST") {
+        "Reset".to_string()
+    } else {
+        format!("Bit#({})", width)
+    }
+}
+
+/// Adds the instances found in the Verilog that bluespec.json lacks, and reports where the
+/// two disagree on an instance's type, its ports or their widths. Instances only
+/// bluespec.json has are expected: bsc inlines registers, RWires and the like.
+fn merge_verilog_blocks(verilog_modules: Vec<VerilogModule>, bsv_modules: &mut HashMap<String, ModuleData>, bsv_typedefs: &HashMap<String, TypeStructure>) {
+    for verilog_module in verilog_modules {
+        let module_data = bsv_modules.entry(verilog_module.name.clone()).or_insert_with(|| ModuleData { blocks: HashMap::new() });
+        for instance in verilog_module.instances {
+            let Some(block) = module_data.blocks.get(&instance.name) else {
+                debug!("Verilog: Adding {}.{} of type {}", verilog_module.name, instance.name, instance.module_type);
+                let ports = instance.ports.iter()
+                    .filter_map(|(port, width)| match width {
+                        Some(width) => Some(RawBlockPort { name: port.clone(), type_name: verilog_port_type(port, *width) }),
+                        None => {
+                            warn!("Verilog: Skipping {}.{}${}, it is unconnected or its width is unknown", verilog_module.name, instance.name, port);
+                            None
+                        }
+                    })
+                    .collect();
+                module_data.blocks.insert(instance.name, RawBlockDefinition { block_type_name: instance.module_type, ports });
+                continue;
+            };
+
+            // bluetcl names the register primitive (RegN, RegUN, ...) bsc didn't instantiate
+            if !instance.inlined && block.block_type_name != instance.module_type {
+                warn!("Verilog mismatch: {}.{} is a {} in the Verilog but a {} in bluespec.json",
+                    verilog_module.name, instance.name, instance.module_type, block.block_type_name);
+            }
+            for (port, width) in &instance.ports {
+                let Some(block_port) = block.ports.iter().find(|p| p.name == *port) else {
+                    warn!("Verilog mismatch: {}.{}${} is not in bluespec.json", verilog_module.name, instance.name, port);
+                    continue;
+                };
+                if matches!(block_port.type_name.as_str(), "Clock" | "Reset") {
+                    continue;
+                }
+                let json_width = TypeExpr::parse(&block_port.type_name).and_then(|t| t.width(bsv_typedefs));
+                if let (Some(width), Some(json_width)) = (width, json_width) && *width != json_width {
+                    warn!("Verilog mismatch: {}.{}${} is {} bits in the Verilog but {} ({} bits) in bluespec.json",
+                        verilog_module.name, instance.name, port, width, block_port.type_name, json_width);
+                }
+            }
+            for block_port in &block.ports {
+                if !instance.inlined && !instance.ports.iter().any(|(port, _)| *port == block_port.name) {
+                    warn!("Verilog mismatch: {}.{}${} is not in the Verilog", verilog_module.name, instance.name, block_port.name);
+                }
+            }
+        }
+    }
+}
+
+/// Reads the port type hints: module -> instance -> `{type, ports: {port: type}}`.
+fn load_port_hints(filename: &str) -> HashMap<String, HashMap<String, PortHint>> {
+    let bytes = read_bsv_file(filename);
+    if bytes.is_empty() {
+        return HashMap::new();
+    }
+    match serde_json::from_slice(&bytes) {
+        Ok(hints) => hints,
+        Err(e) => {
+            warn!("Error parsing {}: {}", filename, e);
+            HashMap::new()
+        }
+    }
+}
+
+/// Binds instance ports to the types in the hints, adding the instances and ports that are
+/// missing. A hint whose width differs from a port the Verilog sized is reported.
+fn apply_port_hints(port_hints: HashMap<String, HashMap<String, PortHint>>, bsv_modules: &mut HashMap<String, ModuleData>, bsv_typedefs: &HashMap<String, TypeStructure>) {
+    for (module_name, instances) in port_hints {
+        let module_data = bsv_modules.entry(module_name.clone()).or_insert_with(|| ModuleData { blocks: HashMap::new() });
+        for (instance_name, hint) in instances {
+            let block = module_data.blocks.entry(instance_name.clone()).or_insert_with(|| RawBlockDefinition {
+                block_type_name: hint.type_name.clone().unwrap_or_default(),
+                ports: vec![],
+            });
+            if let Some(type_name) = hint.type_name {
+                block.block_type_name = type_name;
+            }
+            for (port_name, type_name) in hint.ports {
+                let hint_width = TypeExpr::parse(&type_name).and_then(|t| t.width(bsv_typedefs));
+                if hint_width.is_none() {
+                    warn!("Port hint: {}.{}${} has type {}, whose width is unknown", module_name, instance_name, port_name, type_name);
+                }
+                match block.ports.iter_mut().find(|p| p.name == port_name) {
+                    Some(port) => {
+                        let port_width = TypeExpr::parse(&port.type_name).and_then(|t| t.width(bsv_typedefs));
+                        if let (Some(port_width), Some(hint_width)) = (port_width, hint_width) && port_width != hint_width {
+                            warn!("Port hint: {}.{}${} is {} ({} bits) but {} bits wide",
+                                module_name, instance_name, port_name, type_name, hint_width, port_width);
+                        }
+                        port.type_name = type_name;
+                    }
+                    None => block.ports.push(RawBlockPort { name: port_name, type_name }),
+                }
+            }
+        }
+    }
+}
+
+/// Reads the optional radix policy. A missing or broken file leaves every field to Surfer.
+fn load_format_policy(filename: &str) -> FormatPolicy {
+    let bytes = read_bsv_file(filename);
+    if bytes.is_empty() {
+        debug!("No {} found, Bits fields keep Surfer's formatting", filename);
+        return FormatPolicy::default();
+    }
+    match serde_json::from_slice(&bytes) {
+        Ok(file) => build_format_policy(filename, file),
+        Err(e) => {
+            debug!("Error parsing {}: {}", filename, e);
+            FormatPolicy::default()
+        }
+    }
+}
+
+/// Compiles the field patterns of a format file, skipping the ones that aren't valid regexes.
+fn build_format_policy(filename: &str, file: FormatFile) -> FormatPolicy {
+    let by_field = file.fields.into_iter()
+        .filter_map(|rule| match Regex::new(&rule.pattern) {
+            Ok(pattern) => Some((pattern, rule.radix)),
+            Err(e) => {
+                debug!("Skipping field pattern '{}' in {}: {}", rule.pattern, filename, e);
+                None
+            }
+        })
+        .collect();
+
+    FormatPolicy {
+        by_type: file.types,
+        by_field,
+        by_width: file.widths,
+        complex_combined: file.complex_combined,
+    }
+}
+
 pub fn initialize_static_data() -> Result<(), Box<dyn std::error::Error>> {
     let bsv_file_bytes = read_bsv_file("bluespec.json");
     let map_file_bytes = read_bsv_file("bluespec_map.json");
     
-    if bsv_file_bytes.is_empty() { return Err(Box::<dyn std::error::Error>::from("Failed to read bluespec.json")); }
+    let source_types = load_bsv_sources("bluespec_sources.json");
+    let verilog_modules = load_verilog("bluespec_verilog.json");
+    let port_hints = load_port_hints("bluespec_ports.json");
+    
+    if bsv_file_bytes.is_empty() && source_types.typedefs.is_empty() && verilog_modules.is_empty() && port_hints.is_empty() { return Err(Box::<dyn std::error::Error>::from("Failed to read bluespec.json")); }
     if map_file_bytes.is_empty() { debug!("Warning: Failed to read bluespec_map.json. Scope path resolution may be limited."); }
 
-    let file_content: DesignFile = serde_json::from_slice(&bsv_file_bytes)?; 
+    // Without bluespec.json the types come from the BSV sources, the instances from the Verilog
+    // and the port types from the hints
+    let file_content: DesignFile = if bsv_file_bytes.is_empty() {
+        debug!("Warning: Failed to read bluespec.json, using the BSV sources, Verilog and port hints");
+        DesignFile::default()
+    } else {
+        read_design_file("bluespec.json", &bsv_file_bytes)?
+    };
     let map_content: ModuleMapContent = serde_json::from_slice(&map_file_bytes)?;
 
     let mut bsv_modules_map = HashMap::new();
-    let mut bsv_typedefs = HashMap::new(); 
-    let mut bsv_lookup = HashMap::new(); 
 
     // --- Process Typedefs and Blocks ---
+    let mut module_typedefs = Vec::new();
     for (module_name, module_content) in file_content.modules {
-        for (type_name, raw_value_ref) in module_content.typedefs.iter() {
-            if let Err(e) = process_typedef(type_name, raw_value_ref.clone(), &mut bsv_typedefs, &mut bsv_lookup) {
-                debug!("Error processing typedef '{}': {}", type_name, e);
-            }
-        }
+        let (typedefs, lookup) = process_module_typedefs(module_content.typedefs);
+        module_typedefs.push((module_name.clone(), typedefs, lookup));
         
         let module_blocks = process_module_blocks(module_content.blocks)?; 
         
         bsv_modules_map.insert(module_name, ModuleData { blocks: module_blocks });
     }
+    let (mut bsv_typedefs, mut bsv_lookup) = merge_module_typedefs(&file_content.top, module_typedefs, &mut bsv_modules_map);
+
+    // --- Typedefs bluetcl didn't report, from the BSV sources ---
+    add_source_types(source_types, &mut bsv_typedefs, &mut bsv_lookup);
+
+    // --- Enum and union tags from bsc itself, when the -dsyminitial dump is present ---
+    apply_dsyminitial("dsyminitial.log", &mut bsv_typedefs, &mut bsv_lookup);
+
+    // --- User layouts for hand-written Bits instances win over generated ones ---
+    apply_overrides("bluespec_overrides.json", &mut bsv_typedefs, &mut bsv_lookup);
+
+    // --- Enum widths and encodings ---
+    fix_enum_widths(&mut bsv_typedefs);
+    mark_one_hot_enums(&file_content.one_hot_enums, &mut bsv_typedefs);
+    apply_enum_kinds(&file_content.enum_kinds, &mut bsv_typedefs);
+
+    // --- Resolve polymorphic field types (e.g. Bit#(numeric type a)) from their widths ---
+    instantiate_field_types(&mut bsv_typedefs, &mut bsv_lookup);
+
+    // --- Instances and port widths from the generated Verilog ---
+    merge_verilog_blocks(verilog_modules, &mut bsv_modules_map, &bsv_typedefs);
+
+    // --- Port types given by hand ---
+    apply_port_hints(port_hints, &mut bsv_modules_map, &bsv_typedefs);
+
+    // --- Resolve types known only by name (e.g. Tuple2#(Bit#(8), Bool) ports) ---
+    for type_name in referenced_type_names(&bsv_modules_map, &bsv_typedefs) {
+        resolve_type_name(&type_name, &mut bsv_typedefs, &mut bsv_lookup);
+    }
+    for type_name in &file_content.text_types {
+        resolve_type_name(type_name, &mut bsv_typedefs, &mut bsv_lookup);
+    }
+    mark_text_types(&file_content.text_types, &mut bsv_typedefs, &mut bsv_lookup);
+
+    // --- Group the ports of synthesized submodule instances into methods ---
+    register_interfaces(&bsv_modules_map, &mut bsv_typedefs, &mut bsv_lookup);
     debug!("bsv_typedefs {:?}",bsv_typedefs);
     debug!("bsv_lookup {:?}",bsv_lookup);
     debug!("bsv_modules {:?}",bsv_modules_map);
@@ -326,7 +1528,327 @@ pub fn initialize_static_data() -> Result<(), Box<dyn std::error::Error>> {
     // --- Process Maps ---
     let mut maps_guard = BSV_MAPS.write().unwrap();
     *maps_guard = map_content.maps.into_iter().map(|(k, ModuleMapping(v))| (k, v)).collect();
+    // --- Process Format Policy ---
+    *BSV_FORMATS.write().unwrap() = load_format_policy("bluespec_format.json");
 
     Ok(())
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::translators::translate_segment;
+    use surfer_translation_types::{TranslationResult, ValueRepr};
+
+    fn port(name: &str, type_name: &str) -> RawBlockPort {
+        RawBlockPort { name: name.to_string(), type_name: type_name.to_string() }
+    }
+
+    fn field(name: &str, msb: usize, lsb: usize, type_name: &str) -> TypeSegment {
+        TypeSegment { name: Some(name.to_string()), msb, lsb, type_name: type_name.to_string(), nested_structure: None }
+    }
+
+    fn module(blocks: &[(&str, &str, Vec<RawBlockPort>)]) -> ModuleData {
+        ModuleData {
+            blocks: blocks.iter().map(|(name, block_type, ports)| {
+                (name.to_string(), RawBlockDefinition { block_type_name: block_type.to_string(), ports: ports.clone() })
+            }).collect(),
+        }
+    }
+
+    /// `(name, msb, lsb, type)` of every top-level field.
+    fn fields(structure: &TypeStructure) -> Vec<(String, usize, usize, String)> {
+        structure.segments.iter()
+            .map(|s| (s.name.clone().unwrap_or_default(), s.msb, s.lsb, s.type_name.clone()))
+            .collect()
+    }
+
+    fn expected(fields: &[(&str, usize, usize, &str)]) -> Vec<(String, usize, usize, String)> {
+        fields.iter().map(|(name, msb, lsb, type_name)| (name.to_string(), *msb, *lsb, type_name.to_string())).collect()
+    }
+
+    /// Decodes `bits` (MSB first, `_` ignored) as one value of `type_name`.
+    fn translate(type_name: &str, bits: &str, bsv_lookup: &HashMap<String, TypeCategory>, bsv_typedefs: &HashMap<String, TypeStructure>) -> TranslationResult {
+        let digits: Vec<char> = bits.chars().filter(|c| *c != '_').collect();
+        let segment = TypeSegment { name: None, msb: digits.len() - 1, lsb: 0, type_name: type_name.to_string(), nested_structure: None };
+        translate_segment(&segment, &digits, digits.len(), bsv_lookup, bsv_typedefs)
+    }
+
+    fn shown(result: &TranslationResult) -> String {
+        match &result.val {
+            ValueRepr::String(text) => text.clone(),
+            ValueRepr::Bits(_, digits) => digits.clone(),
+            other => format!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn methods_group_their_ports() {
+        let ports = vec![
+            port("CLK", "Clock"), port("RST_N", "Reset"),
+            port("in_x", "Bit#(4)"), port("in_b", "Bool"), port("EN_in", "Bool"), port("RDY_in", "Bool"),
+            port("out", "Bit#(8)"), port("RDY_out", "Bool"),
+        ];
+        let methods = group_method_ports(&ports);
+        assert_eq!(methods.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(), ["in", "out"]);
+        let write = &methods[0];
+        assert_eq!(write.enable.as_ref().map(|p| p.name.as_str()), Some("EN_in"));
+        assert_eq!(write.ready.as_ref().map(|p| p.name.as_str()), Some("RDY_in"));
+        assert_eq!(write.arguments.iter().map(|(name, p)| (name.as_str(), p.name.as_str())).collect::<Vec<_>>(), [("x", "in_x"), ("b", "in_b")]);
+        assert!(write.result.is_none());
+        let read = &methods[1];
+        assert!(read.enable.is_none());
+        assert_eq!(read.result.as_ref().map(|p| p.name.as_str()), Some("out"));
+
+        let mut bsv_modules = HashMap::new();
+        bsv_modules.insert("mkTop".to_string(), module(&[
+            ("aa_inst_a", "mkA", ports),
+            // Primitives have no EN_/RDY_ methods
+            ("rb", "RegN", vec![port("D_IN", "Bit#(8)"), port("Q_OUT", "Bit#(8)")]),
+        ]));
+        let mut bsv_typedefs = HashMap::new();
+        let mut bsv_lookup = HashMap::from([("Bool".to_string(), TypeCategory::Bool)]);
+        register_interfaces(&bsv_modules, &mut bsv_typedefs, &mut bsv_lookup);
+        assert!(!bsv_typedefs.contains_key("RegN"));
+        assert!(matches!(bsv_lookup.get("mkA"), Some(TypeCategory::Interface)));
+
+        // The first method in the MSBs, each as {EN, RDY, arguments..., result}
+        let mk_a = &bsv_typedefs["mkA"];
+        assert_eq!(mk_a.total_width, 16);
+        assert_eq!(fields(mk_a), expected(&[("in", 15, 9, "Compound"), ("out", 8, 0, "Compound")]));
+        assert_eq!(fields(mk_a.segments[0].nested_structure.as_ref().unwrap()),
+            expected(&[("EN", 6, 6, "Bool"), ("RDY", 5, 5, "Bool"), ("x", 4, 1, "Bit#(4)"), ("b", 0, 0, "Bool")]));
+        assert_eq!(fields(mk_a.segments[1].nested_structure.as_ref().unwrap()),
+            expected(&[("RDY", 8, 8, "Bool"), ("result", 7, 0, "Bit#(8)")]));
+
+        let value = translate("mkA", "1_0_0101_1_1_00000011", &bsv_lookup, &bsv_typedefs);
+        assert_eq!(shown(&value), "mkA { in: { EN: True, RDY: False, x: 'h5, b: True }, out: { RDY: True, result: 'h03 } }");
+    }
+
+    #[test]
+    fn interfaces_need_known_port_widths() {
+        let mut bsv_modules = HashMap::new();
+        bsv_modules.insert("mkTop".to_string(), module(&[
+            ("a", "mkA", vec![port("EN_put", "Bool"), port("put_x", "test1::Unknown_t")]),
+        ]));
+        let mut bsv_typedefs = HashMap::new();
+        let mut bsv_lookup = HashMap::new();
+        register_interfaces(&bsv_modules, &mut bsv_typedefs, &mut bsv_lookup);
+        assert!(bsv_typedefs.is_empty());
+        assert!(bsv_lookup.is_empty());
+    }
+
+    #[test]
+    fn tuples_put_the_first_component_in_the_msbs() {
+        let mut bsv_typedefs = HashMap::new();
+        let mut bsv_lookup = HashMap::new();
+        resolve_type_name("Tuple3#(Bit#(4), Bool, UInt#(3))", &mut bsv_typedefs, &mut bsv_lookup);
+        let tuple = &bsv_typedefs["Tuple3#(Bit#(4), Bool, UInt#(3))"];
+        assert_eq!(tuple.total_width, 8);
+        assert_eq!(fields(tuple), expected(&[("tpl_1", 7, 4, "Bit#(4)"), ("tpl_2", 3, 3, "Bool"), ("tpl_3", 2, 0, "UInt#(3)")]));
+
+        let value = translate("Tuple3#(Bit#(4), Bool, UInt#(3))", "1010_1_011", &bsv_lookup, &bsv_typedefs);
+        let parts: Vec<String> = value.subfields.iter().map(|f| format!("{}={}", f.name, shown(&f.result))).collect();
+        assert_eq!(parts, ["tpl_1=1010", "tpl_2=True", "tpl_3=3"]);
+    }
+
+    #[test]
+    fn either_is_left_or_right() {
+        let mut bsv_typedefs = HashMap::new();
+        let mut bsv_lookup = HashMap::new();
+        resolve_type_name("Either#(Bit#(4), Bool)", &mut bsv_typedefs, &mut bsv_lookup);
+        assert!(matches!(bsv_lookup.get("Either#(Bit#(4), Bool)"), Some(TypeCategory::Union)));
+        assert_eq!(bsv_typedefs["Either#(Bit#(4), Bool)"].total_width, 5);
+
+        let left = translate("Either#(Bit#(4), Bool)", "0_1010", &bsv_lookup, &bsv_typedefs);
+        assert_eq!(shown(&left), "tagged Left 'ha");
+        assert_eq!(shown(&left.subfields[1].result), "1010");
+        assert_eq!(left.subfields[2].result.val, ValueRepr::NotPresent);
+        // The narrower arm sits in the MSBs of the payload
+        let right = translate("Either#(Bit#(4), Bool)", "1_1000", &bsv_lookup, &bsv_typedefs);
+        assert_eq!(shown(&right), "tagged Right True");
+    }
+
+    #[test]
+    fn built_in_numbers_need_no_typedef() {
+        let mut bsv_modules = HashMap::new();
+        bsv_modules.insert("mkTop".to_string(), module(&[
+            ("count", "RegN", vec![port("D_IN", "Int#(8)")]),
+            ("level", "RegN", vec![port("D_IN", "UInt#(4)")]),
+            ("gain", "RegN", vec![port("D_IN", "FixedPoint#(4, 4)")]),
+            ("busy", "RegN", vec![port("D_IN", "Bool")]),
+        ]));
+        let mut bsv_typedefs = HashMap::new();
+        let mut bsv_lookup = HashMap::new();
+        for type_name in referenced_type_names(&bsv_modules, &bsv_typedefs) {
+            resolve_type_name(&type_name, &mut bsv_typedefs, &mut bsv_lookup);
+        }
+        assert!(matches!(bsv_lookup.get("Int#(8)"), Some(TypeCategory::Int)));
+        assert!(matches!(bsv_lookup.get("UInt#(4)"), Some(TypeCategory::UInt)));
+        assert!(matches!(bsv_lookup.get("FixedPoint#(4, 4)"), Some(TypeCategory::FixedPoint)));
+        assert!(matches!(bsv_lookup.get("Bool"), Some(TypeCategory::Bool)));
+        assert_eq!(bsv_typedefs["Int#(8)"].total_width, 8);
+        assert_eq!(bsv_typedefs["FixedPoint#(4, 4)"].total_width, 8);
+
+        assert_eq!(shown(&translate("Int#(8)", "11111110", &bsv_lookup, &bsv_typedefs)), "-2");
+        assert_eq!(shown(&translate("UInt#(4)", "1110", &bsv_lookup, &bsv_typedefs)), "14");
+        assert_eq!(shown(&translate("FixedPoint#(4, 4)", "0001_1000", &bsv_lookup, &bsv_typedefs)), "1.5");
+    }
+
+    #[test]
+    fn overrides() {
+        let generated = || TypeStructure {
+            total_width: 14,
+            segments: vec![field("rgb", 13, 8, "test1::Colors_e"), field("b", 7, 0, "Bit#(8)")],
+            enum_definition: None,
+            union_definition: None,
+            vector_definition: None,
+        };
+        let mut bsv_typedefs = HashMap::from([
+            ("test1::Foo_st".to_string(), generated()),
+            ("test1::Bar_st".to_string(), generated()),
+        ]);
+        let mut bsv_lookup = HashMap::new();
+        let overrides = serde_json::from_str(r#"{
+            "test1::Foo_st": {"width": 14, "fields": [
+                {"name": "rgb", "msb": 13, "lsb": 12, "type": "test1::Colors_e"},
+                {"name": "b", "msb": 7, "lsb": 0, "type": "Bit#(8)"}]},
+            "test1::New_t": {"fields": [
+                {"name": "hi", "msb": 7, "lsb": 4, "type": "UInt#(4)"},
+                {"name": "lo", "msb": 3, "lsb": 0, "type": "UInt#(4)"}]},
+            "test1::Bar_st": {"fields": [
+                {"name": "a", "msb": 7, "lsb": 2, "type": "Bit#(6)"},
+                {"name": "b", "msb": 3, "lsb": 0, "type": "Bit#(4)"}]},
+            "test1::Wide_t": {"width": 4, "fields": [{"name": "a", "msb": 5, "lsb": 0, "type": "Bit#(6)"}]}
+        }"#).unwrap();
+        merge_overrides(overrides, &mut bsv_typedefs, &mut bsv_lookup);
+
+        // An override replaces the generated layout, padding included
+        let foo = &bsv_typedefs["test1::Foo_st"];
+        assert_eq!(foo.total_width, 14);
+        assert_eq!(fields(foo), expected(&[("rgb", 13, 12, "test1::Colors_e"), ("b", 7, 0, "Bit#(8)")]));
+        // Types only the overrides know are added, with their field types
+        assert_eq!(bsv_typedefs["test1::New_t"].total_width, 8);
+        assert!(matches!(bsv_lookup.get("test1::New_t"), Some(TypeCategory::Struct)));
+        assert!(matches!(bsv_lookup.get("UInt#(4)"), Some(TypeCategory::UInt)));
+        // Overlapping or overflowing fields reject the override; the generated layout stays
+        assert_eq!(bsv_typedefs["test1::Bar_st"], generated());
+        assert!(!bsv_typedefs.contains_key("test1::Wide_t"));
+        assert!(!bsv_lookup.contains_key("test1::Wide_t"));
+    }
+
+    #[test]
+    fn top_module_layout_wins() {
+        let foo = |width: usize| TypeStructure {
+            total_width: width,
+            segments: vec![field("b", width - 1, 0, "Bit#(8)")],
+            enum_definition: None,
+            union_definition: None,
+            vector_definition: None,
+        };
+        let bar = TypeStructure {
+            total_width: 9,
+            segments: vec![field("f", 8, 1, "test1::Foo_st"), field("ok", 0, 0, "Bool")],
+            enum_definition: None,
+            union_definition: None,
+            vector_definition: None,
+        };
+        let typedefs = |foo_width: usize| -> ModuleTypedefs {
+            let typedefs = HashMap::from([
+                ("test1::Foo_st".to_string(), foo(foo_width)),
+                ("test1::Bar_st".to_string(), bar.clone()),
+                ("Bool".to_string(), foo(1)),
+            ]);
+            let lookup = typedefs.keys().map(|k| (k.clone(), TypeCategory::Struct)).collect();
+            (String::new(), typedefs, lookup)
+        };
+
+        let mut bsv_modules = HashMap::from([
+            ("mkTop".to_string(), module(&[("f", "RegN", vec![port("D_IN", "test1::Foo_st")])])),
+            ("mkA".to_string(), module(&[("f", "RegN", vec![port("D_IN", "test1::Foo_st")]), ("g", "RegN", vec![port("D_IN", "Bool")])])),
+        ]);
+        let (mk_a, mk_top) = (typedefs(12), typedefs(8));
+        let module_typedefs = vec![
+            ("mkA".to_string(), mk_a.1, mk_a.2),
+            ("mkTop".to_string(), mk_top.1, mk_top.2),
+        ];
+        let (bsv_typedefs, bsv_lookup) = merge_module_typedefs("mkTop", module_typedefs, &mut bsv_modules);
+
+        assert_eq!(bsv_typedefs["test1::Foo_st"], foo(8));
+        assert_eq!(bsv_typedefs["mkA::test1::Foo_st"], foo(12));
+        // Types naming a renamed type follow it; the rest stay shared
+        assert_eq!(bsv_typedefs["test1::Bar_st"], bar);
+        assert_eq!(fields(&bsv_typedefs["mkA::test1::Bar_st"]), expected(&[("f", 8, 1, "mkA::test1::Foo_st"), ("ok", 0, 0, "Bool")]));
+        assert!(!bsv_typedefs.contains_key("mkA::Bool"));
+        assert!(matches!(bsv_lookup.get("mkA::test1::Foo_st"), Some(TypeCategory::Struct)));
+        // Only the other module's ports move to its own layout
+        assert_eq!(bsv_modules["mkA"].blocks["f"].ports[0].type_name, "mkA::test1::Foo_st");
+        assert_eq!(bsv_modules["mkA"].blocks["g"].ports[0].type_name, "Bool");
+        assert_eq!(bsv_modules["mkTop"].blocks["f"].ports[0].type_name, "test1::Foo_st");
+
+        // Unqualified field types resolve to the package's own type, not a module's copy
+        assert_eq!(resolve_field_type("Foo_st", 8, &bsv_typedefs).as_deref(), Some("test1::Foo_st"));
+    }
+
+    #[test]
+    fn without_the_top_module_the_first_by_name_wins() {
+        let foo = |width: usize| TypeStructure {
+            total_width: width,
+            segments: vec![field("b", width - 1, 0, "Bit#(8)")],
+            enum_definition: None,
+            union_definition: None,
+            vector_definition: None,
+        };
+        let module_typedefs = |name: &str, width: usize| -> ModuleTypedefs {
+            (name.to_string(), HashMap::from([("test1::Foo_st".to_string(), foo(width))]), HashMap::new())
+        };
+        let mut bsv_modules = HashMap::new();
+        let (bsv_typedefs, _) = merge_module_typedefs("mkTop", vec![module_typedefs("mkB", 4), module_typedefs("mkA", 6)], &mut bsv_modules);
+        assert_eq!(bsv_typedefs["test1::Foo_st"], foo(6));
+        assert_eq!(bsv_typedefs["mkB::test1::Foo_st"], foo(4));
+        assert!(!bsv_typedefs.contains_key("mkA::test1::Foo_st"));
+    }
+
+    #[test]
+    fn enum_kinds_colour_the_decoded_value() {
+        let members = HashMap::from([(1, "Red".to_string()), (2, "Blue".to_string()), (3, "Green".to_string())]);
+        let mut bsv_typedefs = HashMap::from([("test1::Colors_e".to_string(), build_enum_structure("test1::Colors_e", 2, members))]);
+        let bsv_lookup = HashMap::from([("test1::Colors_e".to_string(), TypeCategory::Enum)]);
+        let enum_kinds = HashMap::from([("test1::Colors_e".to_string(), HashMap::from([
+            ("Red".to_string(), "error".to_string()),
+            ("Blue".to_string(), "#ff8000".to_string()),
+            ("Green".to_string(), "loud".to_string()),
+        ]))]);
+        apply_enum_kinds(&enum_kinds, &mut bsv_typedefs);
+
+        assert!(matches!(translate("test1::Colors_e", "01", &bsv_lookup, &bsv_typedefs).kind, ValueKind::Error));
+        assert_eq!(translate("test1::Colors_e", "10", &bsv_lookup, &bsv_typedefs).kind, ValueKind::Custom(ecolor::Color32::from_rgb(0xff, 0x80, 0)));
+        // Unknown kinds are skipped
+        assert!(matches!(translate("test1::Colors_e", "11", &bsv_lookup, &bsv_typedefs).kind, ValueKind::Normal));
+    }
+
+    #[test]
+    fn format_policy() {
+        let file: FormatFile = serde_json::from_str(r#"{
+            "types": {"test1::Addr": "hex"},
+            "fields": [
+                {"pattern": "^addr", "radix": "hex"},
+                {"pattern": "(", "radix": "binary"},
+                {"pattern": "count$", "radix": "decimal"}],
+            "widths": [{"min_width": 32, "radix": "hex"}, {"max_width": 4, "radix": "binary"}],
+            "complex_combined": true
+        }"#).unwrap();
+        let policy = build_format_policy("bluespec_format.json", file);
+        // The pattern that isn't a regex is skipped
+        assert_eq!(policy.by_field.len(), 2);
+        assert!(policy.complex_combined);
+        // Type, then field name, then width
+        assert_eq!(policy.radix_for("test1::Addr", Some("count"), 2), Some(Radix::Hex));
+        assert_eq!(policy.radix_for("Bit#(2)", Some("addr_lo"), 2), Some(Radix::Hex));
+        assert_eq!(policy.radix_for("Bit#(40)", Some("count"), 40), Some(Radix::Decimal));
+        assert_eq!(policy.radix_for("Bit#(40)", Some("data"), 40), Some(Radix::Hex));
+        assert_eq!(policy.radix_for("Bit#(3)", None, 3), Some(Radix::Binary));
+        assert_eq!(policy.radix_for("Bit#(8)", Some("data"), 8), None);
+    }
+}
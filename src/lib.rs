@@ -21,6 +21,18 @@ mod helper;
 mod ingest;
 // Declares that Cargo should look for translation logic in src/translators.rs
 mod translators;
+// Declares that Cargo should look for the BSV type expression parser in src/type_expr.rs
+mod type_expr;
+// Declares that Cargo should look for the arbitrary width bit vector in src/bitvec.rs
+mod bitvec;
+// Declares that Cargo should look for the bsc -dsyminitial reader in src/dsym.rs
+mod dsym;
+// Declares that Cargo should look for the BSV source typedef parser in src/bsv_source.rs
+mod bsv_source;
+// Declares that Cargo should look for the bsc Verilog reader in src/verilog.rs
+mod verilog;
+// Declares that Cargo should look for the bluespec.json reader in src/design_file.rs
+mod design_file;
 
 
 // --- 3. Public Re-exports (Making sub-module items available to *this* module) ---
@@ -29,6 +41,8 @@ mod translators;
 pub use helper::*;
 pub use ingest::initialize_static_data;
 pub use translators::*;
+pub use type_expr::TypeExpr;
+pub use bitvec::BitVector;
 
 // You may need to explicitly import the functions you need from the new files
 // E.g., for use in the translate/variable_info plugin_fns:
@@ -80,15 +94,18 @@ pub fn translates(variable: VariableMeta<(), ()>) -> FnResult<TranslationPrefere
     debug!("Translates: Category = {:?}",category);
     match category {
         // Types that require structural modification or symbol lookups:
-        Some(TypeCategory::Struct) | Some(TypeCategory::Union) |
-        Some(TypeCategory::Interface) | Some(TypeCategory::Enum) => {
+        Some(TypeCategory::Struct) | Some(TypeCategory::Union) | Some(TypeCategory::Maybe) | Some(TypeCategory::Vector) |
+        Some(TypeCategory::Interface) | Some(TypeCategory::Enum) |
+        Some(TypeCategory::Int) | Some(TypeCategory::UInt) |
+        Some(TypeCategory::FixedPoint) | Some(TypeCategory::Float) |
+        Some(TypeCategory::Bool) | Some(TypeCategory::Text) => {
             // We want to transform these complex types.
             Ok(TranslationPreference::Prefer)
         },
 
         // Primitives: The debugger's default display is often sufficient (raw bits),
         // and we want to avoid the extra function call overhead.
-        Some(TypeCategory::Bits) | Some(TypeCategory::Bool) => {
+        Some(TypeCategory::Bits) => {
             Ok(TranslationPreference::No)
         },
 
@@ -119,25 +136,42 @@ pub fn translate(params: Json<TranslateParams>) -> FnResult<TranslationResult> {
     let type_name = get_variable_type_name(variable)
         .ok_or_else(|| Error::msg(format!("Failed to determine type for variable: {}", variable.var.name)))?;
 
-    let struct_def = {
-        let typedefs_guard = BSV_TYPEDEFS.read().unwrap();
-        typedefs_guard.get(&type_name).cloned()
-            .ok_or_else(|| Error::msg(format!("Struct definition missing for: {}", type_name)))?
-    };
+    // Held for the whole translation: the translators take these maps rather than locking again
+    let bsv_lookup = BSV_LOOKUP.read().unwrap();
+    let bsv_typedefs = BSV_TYPEDEFS.read().unwrap();
+
+    // A top-level Bool stays a bit so Surfer keeps drawing it as a single line
+    if matches!(bsv_lookup.get(&type_name), Some(TypeCategory::Bool)) {
+        return Ok(translate_bool_line(value));
+    }
 
     // 2. Get VCD width and data
     let vcd_width = variable.num_bits.unwrap_or(0) as usize;
+
+    let type_width = {
+        let def = bsv_typedefs.get(&type_name)
+            .ok_or_else(|| Error::msg(format!("Struct definition missing for: {}", type_name)))?;
+        // An enum is as wide as the port carrying it: explicit tags and custom `pack`
+        // mean the tag values alone don't tell us the width
+        if def.enum_definition.is_some() && vcd_width >= def.total_width {
+            vcd_width
+        } else {
+            def.total_width
+        }
+    };
     let digits_str_unpadded = match value {
         VariableValue::BigUint(b) => format!("{:b}", b),
         VariableValue::String(s) => s.clone(),
     //   _ => return Ok(create_no_translation_result()),
     };
 
-    // 3. 🌟 CRITICAL: Pad to TYPE width, NOT VCD width
-    let type_width = struct_def.total_width; // Use the type's actual width
+    // 3. 🌟 CRITICAL: Pad to TYPE width, NOT VCD width. As in VCD, a leading x or z extends
+    // to the left; anything else is zero-extended.
     let padding = type_width.saturating_sub(digits_str_unpadded.len());
-    let digits_str: String = std::iter::repeat('0')
-        .take(padding)
+    let pad_char = digits_str_unpadded.chars().next()
+        .filter(|c| matches!(c, 'x' | 'X' | 'z' | 'Z'))
+        .unwrap_or('0');
+    let digits_str: String = std::iter::repeat_n(pad_char, padding)
         .chain(digits_str_unpadded.chars())
         .collect();
     let digits_vec: Vec<char> = digits_str.chars().collect();
@@ -147,9 +181,22 @@ pub fn translate(params: Json<TranslateParams>) -> FnResult<TranslationResult> {
         debug!("Width mismatch: VCD={} bits, Type={} bits. Using type width.", vcd_width, type_width);
     }
 
-    // 5. Call translator with CORRECT width
-    debug!("Calling TranslateRecursive with {:?} {:?} {:?}",struct_def,type_width,digits_vec);
-    let tr = translate_recursive(&struct_def, type_width, &digits_vec);
+    // 5. Call translator with CORRECT width: the whole value is one segment of the variable's type
+    let segment = TypeSegment {
+        name: None,
+        msb: type_width.saturating_sub(1),
+        lsb: 0,
+        type_name: type_name.clone(),
+        nested_structure: None,
+    };
+    debug!("Calling translate_segment with {:?} {:?} {:?}",segment,type_width,digits_vec);
+    let tr = translate_segment(
+        &segment,
+        &digits_vec,
+        type_width,
+        &bsv_lookup,
+        &bsv_typedefs,
+    );
     //debug!("translate return value {:?}",tr);
     Ok(tr)
 }
@@ -172,8 +219,10 @@ pub fn variable_info(variable: VariableMeta<(), ()>) -> FnResult<VariableInfo> {
     match type_category {
         // Mapped to String because the provided VariableInfo enum lacks an Enum variant.
         TypeCategory::Enum => Ok(VariableInfo::String), 
+        TypeCategory::Int | TypeCategory::UInt |
+        TypeCategory::FixedPoint | TypeCategory::Float => Ok(VariableInfo::Real),
         
-        TypeCategory::Struct => {
+        TypeCategory::Struct | TypeCategory::Vector | TypeCategory::Interface => {
             let bsv_typedefs = BSV_TYPEDEFS.read().unwrap() ;
             
             let struct_def = bsv_typedefs.get(&type_name)
@@ -185,13 +234,34 @@ pub fn variable_info(variable: VariableMeta<(), ()>) -> FnResult<VariableInfo> {
             // warn!("field_info= {:?}",field_info);
             Ok(field_info)
         }
+        TypeCategory::Union => {
+            let bsv_typedefs = BSV_TYPEDEFS.read().unwrap() ;
+
+            let union_def = bsv_typedefs.get(&type_name)
+                .and_then(|d| d.union_definition.as_ref())
+                .ok_or_else(|| Error::msg(format!("Union definition missing for: {}", type_name)))?;
+            Ok(get_union_fields_info(union_def, &bsv_lookup, &bsv_typedefs))
+        }
+        TypeCategory::Maybe => {
+            let bsv_typedefs = BSV_TYPEDEFS.read().unwrap() ;
+            let width = bsv_typedefs.get(&type_name).map(|def| def.total_width)
+                .unwrap_or(variable.num_bits.unwrap_or(0) as usize);
+            let segment = TypeSegment {
+                name: None,
+                msb: width.saturating_sub(1),
+                lsb: 0,
+                type_name: type_name.clone(),
+                nested_structure: None,
+            };
+            Ok(get_segment_info(&segment, &bsv_lookup, &bsv_typedefs))
+        }
+        TypeCategory::Bool => Ok(VariableInfo::Bool),
+        TypeCategory::Text => Ok(VariableInfo::String),
         TypeCategory::Bits => { if variable.num_bits == 1.into() {
             Ok(VariableInfo::Bool)}
             else {
                 Ok(VariableInfo::Bits)}
         }
-
-        _ => Ok(VariableInfo::Bits),
     }
 }
 
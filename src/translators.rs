@@ -6,18 +6,22 @@
 // src/translators.rs (Revised)
 // =========================================================================
 
-use extism_pdk::{debug};
+use extism_pdk::{debug, warn};
 use std::collections::HashMap; // <--- ADDED
 
 // Import necessary types from external crate
 use surfer_translation_types::{
-    SubFieldTranslationResult, TranslationResult, ValueKind, VariableInfo,
+    SubFieldTranslationResult, TranslationResult, ValueKind, VariableInfo, VariableValue,
 };
 
 // --- Import items from helper module (The "Headers") ---
 use crate::helper::{
-    TypeSegment,TypeCategory, TypeStructure, BSV_LOOKUP, BSV_TYPEDEFS,BSVTypedefsGuard,
+    TypeSegment,TypeCategory, TypeStructure, BSV_FORMATS, Radix,
+    UnionDefinition, create_no_translation_result,
 };
+use crate::ingest::maybe_payload_segment;
+use crate::type_expr::TypeExpr;
+use crate::bitvec::BitVector;
 
 
 // --- Public Translation Functions (E0603 errors fixed here by adding `pub`) ---
@@ -38,35 +42,74 @@ pub fn get_struct_fields_info(
     // *** FIX E0599: Changed FieldInfo/StructInfo usage to Compound variant ***
     let subfields: Vec<(String, VariableInfo)> = structure.segments.iter().map(|segment| {
         let name = segment.name.clone().unwrap_or_else(|| "unnamed".to_string());
-        
-        let info = if let Some(nested_structure) = &segment.nested_structure {
-            // Nested structure: recursive call
-            get_struct_fields_info(nested_structure, bsv_lookup, bsv_typedefs)
-        } else {
-            // Simple type or a non-nested struct/enum
-            match bsv_lookup.get(&segment.type_name).unwrap_or(&TypeCategory::Bits) {
-                TypeCategory::Struct => {
-                    if let Some(struct_def) = bsv_typedefs.get(&segment.type_name) {
-                        get_struct_fields_info(struct_def, bsv_lookup, bsv_typedefs)
-                    } else if segment.msb == segment.lsb{
-                        VariableInfo::Bool
-                    }
-                    else {
-                        VariableInfo::Bits
-                    }
-                }
-                // Use String for Enums since the VariableInfo definition lacks Enum
-                TypeCategory::Enum => VariableInfo::String,
-                TypeCategory::Bits => varinfo_bits_or_bool(segment),
-                _ => VariableInfo::Bits, // Default: Bit#(N) or unhandled simple type
-            }
-        };
-
-        (name, info)
+        (name, get_segment_info(segment, bsv_lookup, bsv_typedefs))
     }).collect();
 
     VariableInfo::Compound { subfields }
 }
+
+/// Builds the VariableInfo for a single field.
+pub fn get_segment_info(
+    segment: &TypeSegment,
+    bsv_lookup: &HashMap<String, TypeCategory>,
+    bsv_typedefs: &HashMap<String, TypeStructure>
+) -> VariableInfo {
+    if let Some(nested_structure) = &segment.nested_structure {
+        // Nested structure: recursive call
+        return get_struct_fields_info(nested_structure, bsv_lookup, bsv_typedefs);
+    }
+    // Simple type or a non-nested struct/enum
+    match bsv_lookup.get(&segment.type_name).unwrap_or(&TypeCategory::Bits) {
+        TypeCategory::Struct | TypeCategory::Vector | TypeCategory::Interface => {
+            if let Some(struct_def) = bsv_typedefs.get(&segment.type_name) {
+                get_struct_fields_info(struct_def, bsv_lookup, bsv_typedefs)
+            } else if segment.msb == segment.lsb{
+                VariableInfo::Bool
+            }
+            else {
+                VariableInfo::Bits
+            }
+        }
+        TypeCategory::Union => {
+            match bsv_typedefs.get(&segment.type_name).and_then(|d| d.union_definition.as_ref()) {
+                Some(union_def) => get_union_fields_info(union_def, bsv_lookup, bsv_typedefs),
+                None => VariableInfo::Bits,
+            }
+        }
+        // A Maybe shows its payload directly; Invalid is just a different value.
+        TypeCategory::Maybe => match maybe_payload_segment(&segment.type_name, segment.msb + 1 - segment.lsb) {
+            Some(payload) => get_segment_info(&payload, bsv_lookup, bsv_typedefs),
+            None => VariableInfo::Bits,
+        },
+        // Use String for Enums since the VariableInfo definition lacks Enum
+        TypeCategory::Enum => VariableInfo::String,
+        // Bool fields read True/False rather than a drawn line
+        TypeCategory::Bool | TypeCategory::Text => VariableInfo::String,
+        // Decimal numbers: Real lets Surfer draw them as analog signals
+        TypeCategory::Int | TypeCategory::UInt |
+        TypeCategory::FixedPoint | TypeCategory::Float => VariableInfo::Real,
+        TypeCategory::Bits => varinfo_bits_or_bool(segment),
+    }
+}
+
+/// Builds the VariableInfo for a tagged union: a `tag` field plus one compound per non-void arm.
+pub fn get_union_fields_info(
+    union_def: &UnionDefinition,
+    bsv_lookup: &HashMap<String, TypeCategory>,
+    bsv_typedefs: &HashMap<String, TypeStructure>
+) -> VariableInfo {
+    // Arms are listed in tag (declaration) order
+    let mut tags: Vec<&u64> = union_def.members.keys().collect();
+    tags.sort();
+
+    let mut subfields = vec![("tag".to_string(), VariableInfo::String)];
+    for payload in tags.into_iter().filter_map(|t| union_def.members[t].payload.as_ref()) {
+        let name = payload.name.clone().unwrap_or_else(|| "unnamed".to_string());
+        subfields.push((name, get_segment_info(payload, bsv_lookup, bsv_typedefs)));
+    }
+    VariableInfo::Compound { subfields }
+}
+
 fn varinfo_bits_or_bool(segment: &TypeSegment ) -> VariableInfo {
 
                     if segment.msb == segment.lsb{
@@ -109,37 +152,80 @@ ValueRepr,
 
 // ... other imports ...
 
-/// Translates an enum value using a pre-loaded definition (lock-safe).
-/// The signature accepts the pre-cloned/pre-loaded EnumDefinition structure.
-// src/translators.rs (or wherever translate_enum is defined)
+/// Kind for a field holding unknown (`x`) or high-impedance (`z`) bits; `x` wins over `z`.
+pub fn unknown_kind(chunk_slice: &[char]) -> Option<ValueKind> {
+    if chunk_slice.iter().any(|c| !matches!(c, '0' | '1' | 'z' | 'Z')) {
+        Some(ValueKind::Undef)
+    } else if chunk_slice.iter().any(|c| matches!(c, 'z' | 'Z')) {
+        Some(ValueKind::HighImp)
+    } else {
+        None
+    }
+}
 
+/// Placeholder for a value that cannot be decoded because some of its bits are `x` or `z`.
+pub fn unknown_result(chunk_slice: &[char]) -> Option<TranslationResult> {
+    let kind = unknown_kind(chunk_slice)?;
+    let text = if matches!(kind, ValueKind::HighImp) { "z" } else { "x" };
+    Some(TranslationResult {
+        val: ValueRepr::String(text.to_string()),
+        subfields: vec![],
+        kind,
+    })
+}
+
+/// Translates an enum value using the definitions the caller already holds (lock-safe).
 // Note: This function is assumed to be called with the canonical type name
-pub fn translate_enum(type_name: &str, _width: usize, digits: &str) -> TranslationResult {
+pub fn translate_enum(type_name: &str, _width: usize, digits: &str, bsv_typedefs: &HashMap<String, TypeStructure>) -> TranslationResult {
     debug!("translate_enum: called with type_name {:?}, _width {:?}, digits {:?}", type_name, _width, digits);
-    let typedefs_guard = BSV_TYPEDEFS.read().unwrap();
-    let struct_def = typedefs_guard.get(type_name);
-
-    let members: HashMap<u64, String> = if let Some(def) = struct_def {
-        // 🌟 FIX: Read members from the new `enum_definition` field
-        def.enum_definition.as_ref()
-            .map(|e| e.members.clone())
-            .unwrap_or_default()
-    } else {
-        HashMap::new()
-    };
+    // Never pick a member from bits that aren't known
+    let digit_chars: Vec<char> = digits.chars().collect();
+    if let Some(unknown) = unknown_result(&digit_chars) {
+        return unknown;
+    }
+    let struct_def = bsv_typedefs.get(type_name);
+
+    let enum_def = struct_def.and_then(|def| def.enum_definition.clone());
+    let members: HashMap<u64, String> = enum_def.as_ref().map(|e| e.members.clone()).unwrap_or_default();
+    let member_kinds: HashMap<String, ValueKind> = enum_def.as_ref().map(|e| e.member_kinds.clone()).unwrap_or_default();
+    let one_hot = enum_def.is_some_and(|e| e.one_hot);
+
+    // Tags wider than 64 bits can still name a member when their upper bits are zero
+    let bits = BitVector::from_digits(&digit_chars).unwrap_or_else(|| BitVector::zero(digit_chars.len()));
+    if let Some(name) = bits.to_u64().and_then(|val| members.get(&val)) {
+        return TranslationResult {
+            val: ValueRepr::String(name.clone()),
+            subfields: vec![],
+            kind: member_kinds.get(name).copied().unwrap_or(ValueKind::Normal),
+        };
+    }
 
-    let val = u64::from_str_radix(digits, 2).unwrap_or(0);
-    let name = members.get(&val).cloned().unwrap_or_else(|| format!("Unknown({})", val));
+    // One-hot: more than one bit asserted, report every member that is set
+    if one_hot && !bits.is_zero() {
+        let is_set = |tag: u64| (0..64).filter(|i| tag >> i & 1 == 1).all(|i| bits.bit(i));
+        let mut asserted: Vec<(&u64, &String)> = members.iter()
+            .filter(|(tag, _)| **tag != 0 && is_set(**tag))
+            .collect();
+        asserted.sort();
+        if !asserted.is_empty() {
+            let names: Vec<&str> = asserted.iter().map(|(_, name)| name.as_str()).collect();
+            return TranslationResult {
+                val: ValueRepr::String(names.join("|")),
+                subfields: vec![],
+                kind: ValueKind::Error,
+            };
+        }
+    }
 
-    // ... rest of the function remains the same ...
+    // No member has this tag: the design produced a value the type doesn't allow
     TranslationResult {
-        val: surfer_translation_types::ValueRepr::String(name),
+        val: ValueRepr::String(format!("Unknown({})", bits)),
         subfields: vec![],
-        kind: ValueKind::Normal,
+        kind: ValueKind::Error,
     }
 }
 
-/// Recursively translates a segment of the bitstring based on the struct definition.
+// Recursively translates a segment of the bitstring based on the struct definition.
 // =========================================================================
 // src/translators.rs::translate_recursive (Fixed for Nested Structures)
 // =========================================================================
@@ -149,7 +235,7 @@ pub fn translate_enum(type_name: &str, _width: usize, digits: &str) -> Translati
 // ... (translate_enum function is unchanged) ...
 
 
-/// Recursively translates a segment of the bitstring based on the struct definition.
+// Recursively translates a segment of the bitstring based on the struct definition.
 // src/translators.rs
 
 pub fn translate_data_by_category(
@@ -157,7 +243,8 @@ pub fn translate_data_by_category(
     type_category: &TypeCategory,
     chunk_slice: &[char],
     segment_width: usize,
-    bsv_typedefs_guard: &BSVTypedefsGuard,
+    bsv_lookup_guard: &HashMap<String, TypeCategory>,
+    bsv_typedefs_guard: &HashMap<String, TypeStructure>,
 ) -> TranslationResult {
 
     let chunk_str: String = chunk_slice.iter().collect();
@@ -165,7 +252,7 @@ pub fn translate_data_by_category(
     match type_category {
         // Case 1: ENUM
         TypeCategory::Enum => {
-            let enum_result = translate_enum(&segment.type_name, segment_width, &chunk_str);
+            let enum_result = translate_enum(&segment.type_name, segment_width, &chunk_str, bsv_typedefs_guard);
 
             // Check if translate_enum failed (assumed: it returns an empty string/unknown tag)
             let is_failure = matches!(&enum_result.val, ValueRepr::String(s) if s.is_empty());
@@ -198,56 +285,158 @@ pub fn translate_data_by_category(
                     val: ValueRepr::Bit(bit_char),
                     // val: ValueRepr::Bit('1'),
                     subfields: vec![],
-                    kind: ValueKind::Normal,
+                    kind: unknown_kind(chunk_slice).unwrap_or(ValueKind::Normal),
                 }
+            } else if let Some(formatted) = format_bits(segment, chunk_slice) {
+                formatted
             } else {
-            // Requirement: If data is Bits return Bits
+            // Requirement: If data is Bits return Bits (Surfer shows the unknown digits itself)
             TranslationResult {
                 val: ValueRepr::Bits(segment_width as u64, chunk_str),
                 subfields: vec![],
-                kind: ValueKind::Normal,
+                kind: unknown_kind(chunk_slice).unwrap_or(ValueKind::Normal),
             }
                 }
         }
 
-        // Case 3: COMPOUND (Struct/Union)
-        TypeCategory::Struct => {
+        // Case 2a: BOOL
+        TypeCategory::Bool => translate_bool(chunk_slice),
+
+        // Case 2a: TEXT (Char and configured byte vectors)
+        TypeCategory::Text => {
+            translate_text(bsv_typedefs_guard.get(&segment.type_name), chunk_slice)
+        }
+
+        // Case 2b: INTEGERS (two's complement for Int, plain binary for UInt)
+        TypeCategory::Int | TypeCategory::UInt => {
+            translate_integer(chunk_slice, matches!(type_category, TypeCategory::Int))
+        }
+
+        // Case 2c: REALS (FixedPoint#(i, f), Float/Double/FloatingPoint#(e, m))
+        TypeCategory::FixedPoint | TypeCategory::Float => {
+            let expr = TypeExpr::parse(&segment.type_name);
+            if let Some((_, frac_bits)) = expr.as_ref().and_then(TypeExpr::fixed_point_parts) {
+                translate_fixed_point(chunk_slice, frac_bits)
+            } else if let Some((exp_bits, mant_bits)) = expr.as_ref().and_then(TypeExpr::float_parts) {
+                translate_float(chunk_slice, exp_bits, mant_bits)
+            } else {
+                translate_integer(chunk_slice, false)
+            }
+        }
+
+        // Case 3: COMPOUND (Struct/Union/Vector, and Interface: one field per method)
+        TypeCategory::Struct | TypeCategory::Union | TypeCategory::Vector | TypeCategory::Interface => {
             // Requirement: If data is Compound call translate_compound
             translate_compound(
                 segment,
                 chunk_slice,
                 segment_width,
+                bsv_lookup_guard,
                 bsv_typedefs_guard,
             )
         }
 
-        // Default Fallback
-        _ => {
-            // Defaulting unhandled types (like arrays, or unknown) to Bits
-            debug!("Unhandled TypeCategory {:?} for {}. Falling back to Bits.",
-                  type_category, segment.type_name);
-            TranslationResult {
-                val: ValueRepr::Bits(segment_width as u64, chunk_str),
-                subfields: vec![],
-                kind: ValueKind::Normal,
-            }
+        // Case 4: MAYBE
+        TypeCategory::Maybe => {
+            translate_maybe(
+                segment,
+                chunk_slice,
+                segment_width,
+                bsv_lookup_guard,
+                bsv_typedefs_guard,
+            )
         }
     }
 }
 // src/translators.rs
 
+// Collapsed rows of compound values read like BSV's fshow, cut off at this many characters
+const SUMMARY_MAX_CHARS: usize = 256;
+
+/// A decoded value as it appears inside an fshow-style summary; `None` for absent union arms.
+fn fshow_text(result: &TranslationResult) -> Option<String> {
+    match &result.val {
+        ValueRepr::NotPresent => None,
+        ValueRepr::String(text) => Some(text.clone()),
+        ValueRepr::Bit(bit) => Some(format!("'h{}", bit)),
+        ValueRepr::Bits(_, digits) => {
+            let digits: Vec<char> = digits.chars().collect();
+            Some(BitVector::from_digits(&digits).map_or("x".to_string(), |bits| bits.to_hex().replacen("0x", "'h", 1)))
+        }
+        _ => Some(fshow_fields(&result.subfields)),
+    }
+}
+
+/// `{ rgb: Blue, b: 'h03 }`
+fn fshow_fields(subfields: &[SubFieldTranslationResult]) -> String {
+    let fields: Vec<String> = subfields.iter()
+        .filter_map(|field| Some(format!("{}: {}", field.name, fshow_text(&field.result)?)))
+        .collect();
+    format!("{{ {} }}", fields.join(", "))
+}
+
+fn truncate_summary(text: String) -> String {
+    if text.chars().count() <= SUMMARY_MAX_CHARS {
+        return text;
+    }
+    let mut cut: String = text.chars().take(SUMMARY_MAX_CHARS - 3).collect();
+    cut.push_str("...");
+    cut
+}
+
+/// Replaces the bare Struct/Array value of a decoded compound with its fshow-style
+/// one-line summary: `Foo_st { rgb: Blue, b: 'h03 }`, or `<V 'h1 'h2 'h3 >` for vectors.
+pub fn summarize_compound(type_name: Option<&str>, mut result: TranslationResult) -> TranslationResult {
+    let summary = match result.val {
+        ValueRepr::Array => {
+            let elements: Vec<String> = result.subfields.iter().filter_map(|e| fshow_text(&e.result)).collect();
+            format!("<V {} >", elements.join(" "))
+        }
+        ValueRepr::Struct if is_combined_complex(type_name) => {
+            let part = |name: &str| result.subfields.iter().find(|f| f.name == name).and_then(|f| fshow_text(&f.result));
+            match (part("rel"), part("img")) {
+                (Some(rel), Some(img)) => match img.strip_prefix('-') {
+                    Some(magnitude) => format!("{} - {}i", rel, magnitude),
+                    None => format!("{} + {}i", rel, img),
+                },
+                _ => fshow_fields(&result.subfields),
+            }
+        }
+        ValueRepr::Struct => {
+            // fshow prints the unqualified name: test1::Foo_st -> Foo_st
+            let name = type_name
+                .and_then(TypeExpr::parse)
+                .and_then(|e| e.base_name().map(str::to_string))
+                .or_else(|| type_name.map(str::to_string));
+            match name {
+                Some(name) => format!("{} {}", name, fshow_fields(&result.subfields)),
+                None => fshow_fields(&result.subfields),
+            }
+        }
+        _ => return result,
+    };
+    result.val = ValueRepr::String(truncate_summary(summary));
+    result
+}
+
+fn is_combined_complex(type_name: Option<&str>) -> bool {
+    type_name.and_then(TypeExpr::parse).is_some_and(|e| e.complex_part().is_some())
+        && BSV_FORMATS.read().unwrap().complex_combined
+}
+
 pub fn translate_compound(
     segment: &TypeSegment,
     chunk_slice: &[char],
     segment_width: usize,
-    bsv_typedefs_guard: &BSVTypedefsGuard,
+    bsv_lookup_guard: &HashMap<String, TypeCategory>,
+    bsv_typedefs_guard: &HashMap<String, TypeStructure>,
 ) -> TranslationResult {
 
     // 1. Lookup the structure definition by name
     if let Some(struct_def) = bsv_typedefs_guard.get(&segment.type_name) {
         // Requirement: For each segment call translate_recursive()
         debug!("TR-RECURSE: Entering Compound struct '{}'", segment.type_name);
-        translate_recursive(struct_def, segment_width, chunk_slice)
+        summarize_compound(Some(&segment.type_name), translate_recursive(struct_def, segment_width, chunk_slice, bsv_lookup_guard, bsv_typedefs_guard))
     } else {
         // Compound type category found, but definition is missing -> Fallback
         debug!("TR-FAIL: Struct category found for '{}', but typedef is missing. Falling back to Bits.",
@@ -257,7 +446,7 @@ pub fn translate_compound(
         TranslationResult {
             val: ValueRepr::Bits(segment_width as u64, chunk_str),
             subfields: vec![],
-            kind: ValueKind::Normal,
+            kind: unknown_kind(chunk_slice).unwrap_or(ValueKind::Normal),
         }
     }
 }
@@ -267,89 +456,434 @@ pub fn translate_recursive(
     structure: &TypeStructure,
     total_width: usize,
     digits: &[char],
+    bsv_lookup_guard: &HashMap<String, TypeCategory>,
+    bsv_typedefs_guard: &HashMap<String, TypeStructure>,
 ) -> TranslationResult {
 
     // 1. Handle top-level Enum translation (Immediate exit for non-struct types)
-    if structure.enum_definition.is_some() {
-        if let Some(segment) = structure.segments.first() {
-            let chunk_str: String = digits.iter().collect();
-            return translate_enum(&segment.type_name, total_width, &chunk_str);
-        }
+    if structure.enum_definition.is_some()
+        && let Some(segment) = structure.segments.first()
+    {
+        let chunk_str: String = digits.iter().collect();
+        return translate_enum(&segment.type_name, total_width, &chunk_str, bsv_typedefs_guard);
+    }
+    // 1b. Tagged unions decode the tag first and only then the active arm
+    if let Some(union_def) = &structure.union_definition {
+        return translate_union(union_def, total_width, digits, bsv_lookup_guard, bsv_typedefs_guard);
     }
-
-    // 2. Acquire Locks
-    let bsv_lookup_guard = BSV_LOOKUP.read().unwrap();
-    let bsv_typedefs_guard = BSV_TYPEDEFS.read().unwrap();
 
     let mut subfields = Vec::new();
-    let mut processed_width =0;
+    // digits[0] holds the structure's highest bit; fields are located by their msb,
+    // so vector elements can be listed [0]..[n-1] even though [0] sits in the LSBs.
+    // An override may declare a width above its highest field, so count from the width.
+    let top_msb = structure.segments.iter().map(|s| s.msb).max().unwrap_or(0);
+    let top_msb = structure.total_width.saturating_sub(1).max(top_msb);
 
     // 3. Iterate and Process Segments
     for segment in structure.segments.iter() {
         let segment_width = (segment.msb + 1).saturating_sub(segment.lsb);
 
         // 3a. Check boundaries and extract chunk (using the helper from the prior refactoring)
-        let Some((chunk_slice, new_processed_width)) = extract_bit_chunk(
-            digits, segment_width, processed_width
+        let Some(chunk_slice) = extract_bit_chunk(
+            digits, segment_width, top_msb - segment.msb
         ) else {
-            break;
+            debug!("TR-FAIL: Field '{:?}' lies outside the {} available bits", segment.name, digits.len());
+            continue;
         };
-        processed_width = new_processed_width;
 
         let name = segment.name.clone().unwrap_or_else(|| "unnamed".to_string());
 
         // 3b. Delegate Translation Logic
-        let result =
-            // 🌟 PRIORITY 1: Inlined Nested Structure (e.g., Compound type without a name lookup)
-            if let Some(ref nested_struct_def) = segment.nested_structure {
-                debug!("TR-RECURSE: Recursing into inlined nested structure for field '{}'", name);
-                translate_recursive(nested_struct_def, segment_width, chunk_slice)
-
-            // PRIORITY 2: Global Type Category Lookup
-            } else if let Some(type_category) = bsv_lookup_guard.get(&segment.type_name) {
-                // 🌟 NEW DELEGATION: Call the category-based dispatcher
-                translate_data_by_category(
-                    segment,
-                    type_category,
-                    chunk_slice,
-                    segment_width,
-                    &bsv_typedefs_guard,
-                )
-            }
-            // PRIORITY 3: Unknown Type Name -> Fallback to Bits
-            else {
-                debug!("TR-WARN: Unknown type '{}', falling back to Bits#({})",
-                      segment.type_name, segment_width);
-                let chunk_str: String = chunk_slice.iter().collect();
-                TranslationResult {
-                    val: ValueRepr::Bits(segment_width as u64, chunk_str),
-                    subfields: vec![],
-                    kind: ValueKind::Normal,
-                }
-            };
+        let result = translate_segment(
+            segment,
+            chunk_slice,
+            segment_width,
+            bsv_lookup_guard,
+            bsv_typedefs_guard,
+        );
 
         // 3c. Collect Result
-        subfields.push(SubFieldTranslationResult { name, result: result });
+        subfields.push(SubFieldTranslationResult { name, result });
     }
 
-    // 4. Final Return (Struct assembly)
+    // 4. Final Return (Struct or Vector assembly)
+    // Partially known values keep their known fields; only an entirely x/z value is marked as such
+    let val = if structure.vector_definition.is_some() { ValueRepr::Array } else { ValueRepr::Struct };
+    let all_unknown = digits.iter().all(|c| !matches!(c, '0' | '1'));
     TranslationResult {
-        val: ValueRepr::Struct,
+        val,
         subfields,
+        kind: unknown_kind(digits).filter(|_| all_unknown).unwrap_or(ValueKind::Normal),
+    }
+}
+
+/// Translates a single field, given the bits it occupies.
+pub fn translate_segment(
+    segment: &TypeSegment,
+    chunk_slice: &[char],
+    segment_width: usize,
+    bsv_lookup_guard: &HashMap<String, TypeCategory>,
+    bsv_typedefs_guard: &HashMap<String, TypeStructure>,
+) -> TranslationResult {
+    // 🌟 PRIORITY 1: Inlined Nested Structure (e.g., Compound type without a name lookup)
+    if let Some(ref nested_struct_def) = segment.nested_structure {
+        debug!("TR-RECURSE: Recursing into inlined nested structure for field '{:?}'", segment.name);
+        summarize_compound(None, translate_recursive(nested_struct_def, segment_width, chunk_slice, bsv_lookup_guard, bsv_typedefs_guard))
+
+    // PRIORITY 2: Global Type Category Lookup
+    } else if let Some(type_category) = bsv_lookup_guard.get(&segment.type_name) {
+        // 🌟 NEW DELEGATION: Call the category-based dispatcher
+        translate_data_by_category(
+            segment,
+            type_category,
+            chunk_slice,
+            segment_width,
+            bsv_lookup_guard,
+            bsv_typedefs_guard,
+        )
+    }
+    // PRIORITY 3: Unknown Type Name -> Fallback to Bits
+    else {
+        debug!("TR-WARN: Unknown type '{}', falling back to Bits#({})",
+              segment.type_name, segment_width);
+        if segment_width > 1
+            && let Some(formatted) = format_bits(segment, chunk_slice)
+        {
+            return formatted;
+        }
+        let chunk_str: String = chunk_slice.iter().collect();
+        TranslationResult {
+            val: ValueRepr::Bits(segment_width as u64, chunk_str),
+            subfields: vec![],
+            kind: unknown_kind(chunk_slice).unwrap_or(ValueKind::Normal),
+        }
+    }
+}
+
+/// Renders a `Bool` field as `True`/`False`; `False` is dimmed so asserted flags stand out.
+pub fn translate_bool(chunk_slice: &[char]) -> TranslationResult {
+    let (text, kind) = match chunk_slice.first() {
+        Some('1') => ("True", ValueKind::Normal),
+        Some('0') => ("False", ValueKind::Weak),
+        Some('z' | 'Z') => ("Z", ValueKind::HighImp),
+        _ => ("X", ValueKind::Undef),
+    };
+    TranslationResult {
+        val: ValueRepr::String(text.to_string()),
+        subfields: vec![],
+        kind,
+    }
+}
+
+/// A top-level Bool stays a bit so Surfer keeps drawing it as a single line; `x` and `z`
+/// keep their kind.
+pub fn translate_bool_line(value: &VariableValue) -> TranslationResult {
+    let bit = match value {
+        VariableValue::BigUint(b) => if b.bit(0) { '1' } else { '0' },
+        VariableValue::String(s) => s.chars().last().unwrap_or('x'),
+    };
+    let kind = match bit {
+        '0' | '1' => ValueKind::Normal,
+        _ => translate_bool(&[bit]).kind,
+    };
+    TranslationResult { val: ValueRepr::Bit(bit), subfields: vec![], kind }
+}
+
+/// Renders a Bits field in the radix bluespec_format.json asks for. `None` when no rule
+/// matches or the value has unknown bits, leaving the formatting to Surfer.
+pub fn format_bits(segment: &TypeSegment, chunk_slice: &[char]) -> Option<TranslationResult> {
+    let radix = BSV_FORMATS.read().unwrap()
+        .radix_for(&segment.type_name, segment.name.as_deref(), chunk_slice.len())?;
+    let bits = BitVector::from_digits(chunk_slice)?;
+    let text = match radix {
+        Radix::Binary => bits.to_binary(),
+        Radix::Octal => bits.to_octal(),
+        Radix::Decimal => bits.to_decimal(),
+        Radix::Hex => bits.to_hex(),
+    };
+    Some(TranslationResult {
+        val: ValueRepr::String(text),
+        subfields: vec![],
+        kind: ValueKind::Normal,
+    })
+}
+
+/// Renders `Char` and byte vectors as quoted ASCII with escapes for non-printables.
+/// Vectors read from element `[0]` (the LSBs) up; other types read MSB first.
+pub fn translate_text(structure: Option<&TypeStructure>, chunk_slice: &[char]) -> TranslationResult {
+    if let Some(unknown) = unknown_result(chunk_slice) {
+        return unknown;
+    }
+    let chunk_str: String = chunk_slice.iter().collect();
+    let bytes: Option<Vec<u8>> = chunk_slice.chunks(8)
+        .map(|byte| u8::from_str_radix(&byte.iter().collect::<String>(), 2).ok())
+        .collect();
+    let Some(mut bytes) = bytes.filter(|_| chunk_slice.len().is_multiple_of(8)) else {
+        debug!("TR-FAIL: Cannot read '{}' as text. Falling back to Bits.", chunk_str);
+        return TranslationResult {
+            val: ValueRepr::Bits(chunk_slice.len() as u64, chunk_str),
+            subfields: vec![],
+            kind: ValueKind::Normal,
+        };
+    };
+    if structure.is_some_and(|s| s.vector_definition.is_some()) {
+        bytes.reverse();
+    }
+
+    let text: String = bytes.into_iter().flat_map(std::ascii::escape_default).map(char::from).collect();
+    let quote = if structure.is_some_and(|s| s.total_width == 8 && s.vector_definition.is_none()) { '\'' } else { '"' };
+
+    TranslationResult {
+        val: ValueRepr::String(format!("{quote}{text}{quote}")),
+        subfields: vec![],
+        kind: ValueKind::Normal,
+    }
+}
+
+/// Renders an `Int#(n)` (signed) or `UInt#(n)` as a decimal number.
+pub fn translate_integer(chunk_slice: &[char], signed: bool) -> TranslationResult {
+    if let Some(unknown) = unknown_result(chunk_slice) {
+        return unknown;
+    }
+    let width = chunk_slice.len();
+    let Some(bits) = BitVector::from_digits(chunk_slice) else {
+        let chunk_str: String = chunk_slice.iter().collect();
+        debug!("TR-FAIL: Cannot read '{}' as a {}-bit integer. Falling back to Bits.", chunk_str, width);
+        return TranslationResult {
+            val: ValueRepr::Bits(width as u64, chunk_str),
+            subfields: vec![],
+            kind: ValueKind::Normal,
+        };
+    };
+
+    let text = if signed { bits.to_signed_decimal() } else { bits.to_decimal() };
+
+    TranslationResult {
+        val: ValueRepr::String(text),
+        subfields: vec![],
+        kind: ValueKind::Normal,
+    }
+}
+
+/// Renders a `FixedPoint#(i, f)`: a two's complement number with `f` bits below the binary point.
+/// The value is printed with `{:?}`, which switches to exponent notation for very large or small
+/// magnitudes instead of printing hundreds of digits.
+pub fn translate_fixed_point(chunk_slice: &[char], frac_bits: usize) -> TranslationResult {
+    if let Some(unknown) = unknown_result(chunk_slice) {
+        return unknown;
+    }
+    let width = chunk_slice.len();
+    let Some(bits) = BitVector::from_digits(chunk_slice).filter(|_| width > 0) else {
+        let chunk_str: String = chunk_slice.iter().collect();
+        debug!("TR-FAIL: Cannot read '{}' as a {}-bit fixed point. Falling back to Bits.", chunk_str, width);
+        return TranslationResult {
+            val: ValueRepr::Bits(width as u64, chunk_str),
+            subfields: vec![],
+            kind: ValueKind::Normal,
+        };
+    };
+
+    let (negative, magnitude) = bits.to_signed_magnitude();
+    let value = magnitude.to_f64() / 2f64.powi(frac_bits as i32);
+    let value = if negative { -value } else { value };
+
+    TranslationResult {
+        val: ValueRepr::String(format!("{:?}", value)),
+        subfields: vec![],
+        kind: ValueKind::Normal,
+    }
+}
+
+/// Renders an IEEE-754 style `FloatingPoint#(e, m)` (sign, exponent, mantissa from MSB down),
+/// printed with `{:?}` like [`translate_fixed_point`].
+pub fn translate_float(chunk_slice: &[char], exp_bits: usize, mant_bits: usize) -> TranslationResult {
+    if let Some(unknown) = unknown_result(chunk_slice) {
+        return unknown;
+    }
+    let width = chunk_slice.len();
+    let layout_ok = width == 1 + exp_bits + mant_bits && (2..=30).contains(&exp_bits);
+    let Some(bits) = BitVector::from_digits(chunk_slice).filter(|_| layout_ok) else {
+        let chunk_str: String = chunk_slice.iter().collect();
+        debug!("TR-FAIL: Cannot read '{}' as a float with {} exponent and {} mantissa bits. Falling back to Bits.",
+            chunk_str, exp_bits, mant_bits);
+        return TranslationResult {
+            val: ValueRepr::Bits(width as u64, chunk_str),
+            subfields: vec![],
+            kind: ValueKind::Normal,
+        };
+    };
+
+    let value = match (exp_bits, mant_bits, bits.to_u64()) {
+        (8, 23, Some(raw)) => format!("{:?}", f32::from_bits(raw as u32)),
+        (11, 52, Some(raw)) => format!("{:?}", f64::from_bits(raw)),
+        _ => {
+            let exponent = bits.slice(width - 2, mant_bits);
+            let mantissa = if mant_bits > 0 { bits.slice(mant_bits - 1, 0) } else { BitVector::zero(0) };
+            let bias = (1i32 << (exp_bits - 1)) - 1;
+            let fraction = mantissa.to_f64() / 2f64.powi(mant_bits as i32);
+            let magnitude = if (0..exp_bits).all(|i| exponent.bit(i)) {
+                if mantissa.is_zero() { f64::INFINITY } else { f64::NAN }
+            } else if exponent.is_zero() {
+                fraction * 2f64.powi(1 - bias)
+            } else {
+                (1.0 + fraction) * 2f64.powi(exponent.to_u64().unwrap_or(0) as i32 - bias)
+            };
+            format!("{:?}", if bits.is_negative() { -magnitude } else { magnitude })
+        }
+    };
+
+    TranslationResult {
+        val: ValueRepr::String(value),
+        subfields: vec![],
         kind: ValueKind::Normal,
     }
 }
 
-fn extract_bit_chunk<'a>(
-    digits: &'a [char],
+/// Translates a `Maybe#(T)`: `Invalid` when the valid bit is clear, otherwise the decoded `T`.
+pub fn translate_maybe(
+    segment: &TypeSegment,
+    chunk_slice: &[char],
     segment_width: usize,
-    processed_width: usize,
-) -> Option<(&'a [char], usize)> {
+    bsv_lookup_guard: &HashMap<String, TypeCategory>,
+    bsv_typedefs_guard: &HashMap<String, TypeStructure>,
+) -> TranslationResult {
+    if let Some(unknown) = unknown_result(&chunk_slice[..chunk_slice.len().min(1)]) {
+        return unknown;
+    }
+    if chunk_slice.first() != Some(&'1') {
+        return TranslationResult {
+            val: ValueRepr::String("Invalid".to_string()),
+            subfields: vec![],
+            kind: ValueKind::Undef,
+        };
+    }
+    match maybe_payload_segment(&segment.type_name, segment_width) {
+        Some(payload) => translate_segment(
+            &payload,
+            &chunk_slice[1..],
+            segment_width - 1,
+            bsv_lookup_guard,
+            bsv_typedefs_guard,
+        ),
+        None => {
+            debug!("TR-FAIL: '{}' is too narrow to carry a payload", segment.type_name);
+            let chunk_str: String = chunk_slice.iter().collect();
+            TranslationResult {
+                val: ValueRepr::Bits(segment_width as u64, chunk_str),
+                subfields: vec![],
+                kind: unknown_kind(chunk_slice).unwrap_or(ValueKind::Normal),
+            }
+        }
+    }
+}
 
-    // In an MSB-first array, the start index is simply the width processed so far.
-    let start_idx = processed_width;
+/// Translates a tagged union: decodes the tag, then only the active arm's payload.
+/// Inactive arms are reported as NotPresent; `void` arms produce just the tag.
+pub fn translate_union(
+    union_def: &UnionDefinition,
+    total_width: usize,
+    digits: &[char],
+    bsv_lookup_guard: &HashMap<String, TypeCategory>,
+    bsv_typedefs_guard: &HashMap<String, TypeStructure>,
+) -> TranslationResult {
+    let payload_width = total_width.saturating_sub(union_def.tag_width);
+    if digits.len() < total_width {
+        debug!("TR-FAIL: Union needs {} bits, got {}", total_width, digits.len());
+        return create_no_translation_result();
+    }
+    let (tag_slice, payload_slice) = digits[..total_width].split_at(union_def.tag_width);
+
+    // Without a known tag there is no active arm to decode
+    if let Some(unknown) = unknown_result(tag_slice) {
+        return TranslationResult {
+            val: unknown.val.clone(),
+            kind: unknown.kind,
+            subfields: vec![SubFieldTranslationResult { name: "tag".to_string(), result: unknown }],
+        };
+    }
+    let tag_bits = BitVector::from_digits(tag_slice).unwrap_or_else(|| BitVector::zero(tag_slice.len()));
+    let tag = tag_bits.to_u64().unwrap_or(u64::MAX);
+    let Some(active) = union_def.members.get(&tag) else {
+        debug!("TR-FAIL: No union arm for tag {}", tag_bits);
+        return TranslationResult {
+            val: ValueRepr::String(format!("Unknown({})", tag_bits)),
+            subfields: vec![],
+            kind: ValueKind::Error,
+        };
+    };
 
-    // The end index is the start plus the width of the current segment.
+    let tag_kind = union_def.member_kinds.get(&active.name).copied().unwrap_or(ValueKind::Normal);
+    let mut subfields = vec![SubFieldTranslationResult {
+        name: "tag".to_string(),
+        result: TranslationResult {
+            val: ValueRepr::String(active.name.clone()),
+            subfields: vec![],
+            kind: tag_kind,
+        },
+    }];
+
+    let mut summary = format!("tagged {}", active.name);
+    let mut tags: Vec<&u64> = union_def.members.keys().collect();
+    tags.sort();
+    for member in tags.into_iter().map(|t| &union_def.members[t]) {
+        let Some(payload) = member.payload.as_ref() else { continue };
+        let result = if std::ptr::eq(member, active) {
+            // Payload is left-aligned: msb/lsb are relative to the payload area.
+            let width = (payload.msb + 1).saturating_sub(payload.lsb);
+            let Some(start) = payload_width.checked_sub(payload.msb + 1) else {
+                // A stale or hand-edited bluespec.json: show the payload area undecoded
+                warn!("Union arm '{}' reaches bit {}, outside its {} bit payload. Showing it as Bits.",
+                    member.name, payload.msb, payload_width);
+                let chunk_str: String = payload_slice.iter().collect();
+                subfields.push(SubFieldTranslationResult {
+                    name: member.name.clone(),
+                    result: TranslationResult {
+                        val: ValueRepr::Bits(payload_width as u64, chunk_str),
+                        subfields: vec![],
+                        kind: unknown_kind(payload_slice).unwrap_or(ValueKind::Normal),
+                    },
+                });
+                continue;
+            };
+            let result = translate_segment(
+                payload,
+                &payload_slice[start..start + width],
+                width,
+                bsv_lookup_guard,
+                bsv_typedefs_guard,
+            );
+            // A struct arm reads `tagged MemWrite { addr: .., data: .. }`, anything else `tagged Imm 'h05`
+            let arm_is_struct = matches!(bsv_lookup_guard.get(&payload.type_name), Some(TypeCategory::Struct));
+            if arm_is_struct && !result.subfields.is_empty() {
+                summary = format!("{} {}", summary, fshow_fields(&result.subfields));
+            } else if let Some(text) = fshow_text(&result) {
+                summary = format!("{} {}", summary, text);
+            }
+            result
+        } else {
+            TranslationResult {
+                val: ValueRepr::NotPresent,
+                subfields: vec![],
+                kind: ValueKind::Normal,
+            }
+        };
+        subfields.push(SubFieldTranslationResult { name: member.name.clone(), result });
+    }
+
+    TranslationResult {
+        val: ValueRepr::String(truncate_summary(summary)),
+        subfields,
+        kind: tag_kind,
+    }
+}
+
+fn extract_bit_chunk(
+    digits: &[char],
+    segment_width: usize,
+    start_idx: usize,
+) -> Option<&[char]> {
+
+    // In an MSB-first array, the field starts `start_idx` characters in.
     let end_idx = start_idx + segment_width;
 
     if end_idx > digits.len() {
@@ -357,8 +891,333 @@ fn extract_bit_chunk<'a>(
         return None;
     }
 
-    let chunk_slice = &digits[start_idx..end_idx];
+    Some(&digits[start_idx..end_idx])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helper::{EnumDefinition, UnionMember, VectorDefinition};
+
+    fn digits(bits: &str) -> Vec<char> {
+        bits.chars().filter(|c| *c != '_').collect()
+    }
+
+    fn field(name: &str, msb: usize, lsb: usize, type_name: &str) -> TypeSegment {
+        TypeSegment { name: Some(name.to_string()), msb, lsb, type_name: type_name.to_string(), nested_structure: None }
+    }
+
+    fn layout(total_width: usize, segments: Vec<TypeSegment>) -> TypeStructure {
+        TypeStructure { total_width, segments, enum_definition: None, union_definition: None, vector_definition: None }
+    }
+
+    /// `length` elements of `width` bits, element `[0]` in the LSBs.
+    fn vector(length: usize, element: &str, width: usize) -> TypeStructure {
+        let segments = (0..length).map(|i| field(&format!("[{}]", i), (i + 1) * width - 1, i * width, element)).collect();
+        TypeStructure {
+            vector_definition: Some(VectorDefinition { length, element_type: element.to_string() }),
+            ..layout(length * width, segments)
+        }
+    }
+
+    fn enumeration(type_name: &str, width: usize, members: &[(u64, &str)], one_hot: bool) -> TypeStructure {
+        TypeStructure {
+            enum_definition: Some(EnumDefinition {
+                members: members.iter().map(|(tag, name)| (*tag, name.to_string())).collect(),
+                one_hot,
+                member_kinds: HashMap::new(),
+            }),
+            ..layout(width, vec![TypeSegment { name: None, msb: width - 1, lsb: 0, type_name: type_name.to_string(), nested_structure: None }])
+        }
+    }
+
+    /// `test1::Op`: a 2 bit tag over an 8 bit payload.
+    /// `Imm Bit#(8)` (tag 0), `Flag Bool` (tag 1, left-aligned) and `void Nop` (tag 2).
+    fn op_union() -> TypeStructure {
+        let members = HashMap::from([
+            (0, UnionMember { name: "Imm".to_string(), payload: Some(field("Imm", 7, 0, "Bit#(8)")) }),
+            (1, UnionMember { name: "Flag".to_string(), payload: Some(field("Flag", 7, 7, "Bool")) }),
+            (2, UnionMember { name: "Nop".to_string(), payload: None }),
+        ]);
+        TypeStructure {
+            union_definition: Some(UnionDefinition { tag_width: 2, members, member_kinds: HashMap::new() }),
+            ..layout(10, vec![field("tag", 9, 8, "test1::Op")])
+        }
+    }
+
+    /// Type tables as the ingest leaves them.
+    #[derive(Default)]
+    struct Types {
+        lookup: HashMap<String, TypeCategory>,
+        typedefs: HashMap<String, TypeStructure>,
+    }
+
+    impl Types {
+        fn with(mut self, type_name: &str, category: TypeCategory, structure: Option<TypeStructure>) -> Self {
+            self.lookup.insert(type_name.to_string(), category);
+            if let Some(structure) = structure {
+                self.typedefs.insert(type_name.to_string(), structure);
+            }
+            self
+        }
+
+        /// Decodes `bits` (MSB first, `_` ignored) as one value of `type_name`.
+        fn translate(&self, type_name: &str, bits: &str) -> TranslationResult {
+            let digits = digits(bits);
+            let segment = TypeSegment { name: None, msb: digits.len() - 1, lsb: 0, type_name: type_name.to_string(), nested_structure: None };
+            translate_segment(&segment, &digits, digits.len(), &self.lookup, &self.typedefs)
+        }
+    }
+
+    /// The text a value shows.
+    fn shown(result: &TranslationResult) -> String {
+        match &result.val {
+            ValueRepr::String(text) => text.clone(),
+            ValueRepr::Bits(_, digits) => digits.clone(),
+            ValueRepr::Bit(bit) => bit.to_string(),
+            other => format!("{:?}", other),
+        }
+    }
 
-    // Return the slice and the new total processed width
-    Some((chunk_slice, end_idx))
+    fn subfield<'a>(result: &'a TranslationResult, name: &str) -> &'a TranslationResult {
+        match result.subfields.iter().find(|f| f.name == name) {
+            Some(f) => &f.result,
+            None => panic!("no field {} in {:?}", name, result),
+        }
+    }
+
+    fn field_names(result: &TranslationResult) -> Vec<&str> {
+        result.subfields.iter().map(|f| f.name.as_str()).collect()
+    }
+
+    fn ops() -> Types {
+        Types::default()
+            .with("test1::Op", TypeCategory::Union, Some(op_union()))
+            .with("Bool", TypeCategory::Bool, None)
+    }
+
+    #[test]
+    fn union_decodes_the_tag_then_the_active_arm() {
+        let imm = ops().translate("test1::Op", "00_0000_0101");
+        assert_eq!(shown(&imm), "tagged Imm 'h05");
+        assert_eq!(field_names(&imm), ["tag", "Imm", "Flag"]);
+        assert_eq!(shown(subfield(&imm, "tag")), "Imm");
+        assert_eq!(shown(subfield(&imm, "Imm")), "00000101");
+        assert_eq!(subfield(&imm, "Flag").val, ValueRepr::NotPresent);
+
+        // Narrower arms sit in the MSBs of the payload
+        let flag = ops().translate("test1::Op", "01_1000_0000");
+        assert_eq!(shown(&flag), "tagged Flag True");
+        assert_eq!(shown(subfield(&flag, "Flag")), "True");
+        assert_eq!(subfield(&flag, "Imm").val, ValueRepr::NotPresent);
+    }
+
+    #[test]
+    fn void_arms_are_just_the_tag() {
+        let nop = ops().translate("test1::Op", "10_1111_1111");
+        assert_eq!(shown(&nop), "tagged Nop");
+        assert_eq!(shown(subfield(&nop, "tag")), "Nop");
+        assert!(nop.subfields.iter().skip(1).all(|f| f.result.val == ValueRepr::NotPresent));
+    }
+
+    #[test]
+    fn tags_without_an_arm_are_errors() {
+        let unknown = ops().translate("test1::Op", "11_0000_0000");
+        assert_eq!(shown(&unknown), "Unknown(3)");
+        assert!(matches!(unknown.kind, ValueKind::Error));
+        assert!(unknown.subfields.is_empty());
+    }
+
+    #[test]
+    fn arms_outside_the_payload_show_as_bits() {
+        // A stale layout: Imm claims bits 9..2 of an 8 bit payload
+        let mut stale = op_union();
+        stale.union_definition.as_mut().unwrap().members.get_mut(&0).unwrap().payload = Some(field("Imm", 9, 2, "Bit#(8)"));
+        let types = Types::default().with("test1::Op", TypeCategory::Union, Some(stale));
+        let imm = types.translate("test1::Op", "00_0000_0101");
+        assert_eq!(subfield(&imm, "Imm").val, ValueRepr::Bits(8, "00000101".to_string()));
+    }
+
+    #[test]
+    fn unknown_tags_and_fields() {
+        // No arm can be chosen from an x tag
+        let x_tag = ops().translate("test1::Op", "x0_0000_0101");
+        assert_eq!(shown(&x_tag), "x");
+        assert!(matches!(x_tag.kind, ValueKind::Undef));
+        assert_eq!(field_names(&x_tag), ["tag"]);
+
+        // z in one field leaves its sibling decoded and the struct itself normal
+        let types = Types::default()
+            .with("test1::Pair", TypeCategory::Struct, Some(layout(8, vec![field("a", 7, 4, "UInt#(4)"), field("b", 3, 0, "UInt#(4)")])))
+            .with("UInt#(4)", TypeCategory::UInt, None);
+        let pair = types.translate("test1::Pair", "zzzz_0011");
+        assert_eq!(shown(subfield(&pair, "a")), "z");
+        assert!(matches!(subfield(&pair, "a").kind, ValueKind::HighImp));
+        assert_eq!(shown(subfield(&pair, "b")), "3");
+        assert!(matches!(subfield(&pair, "b").kind, ValueKind::Normal));
+        assert!(matches!(pair.kind, ValueKind::Normal));
+        assert_eq!(shown(&pair), "Pair { a: z, b: 3 }");
+
+        // Only an entirely unknown struct is marked as such
+        assert!(matches!(types.translate("test1::Pair", "xxxx_xxxx").kind, ValueKind::Undef));
+    }
+
+    #[test]
+    fn vector_elements_count_up_from_the_lsbs() {
+        let types = Types::default()
+            .with("Vector#(3, UInt#(4))", TypeCategory::Vector, Some(vector(3, "UInt#(4)", 4)))
+            .with("UInt#(4)", TypeCategory::UInt, None);
+        let v = types.translate("Vector#(3, UInt#(4))", "0011_0010_0001");
+        assert_eq!(field_names(&v), ["[0]", "[1]", "[2]"]);
+        let elements: Vec<String> = v.subfields.iter().map(|f| shown(&f.result)).collect();
+        assert_eq!(elements, ["1", "2", "3"]);
+        assert_eq!(shown(&v), "<V 1 2 3 >");
+
+        let segment = field("v", 11, 0, "Vector#(3, UInt#(4))");
+        match get_segment_info(&segment, &types.lookup, &types.typedefs) {
+            VariableInfo::Compound { subfields } => {
+                assert_eq!(subfields.len(), 3);
+                assert!(subfields.iter().all(|(_, info)| matches!(info, VariableInfo::Real)));
+            }
+            other => panic!("not a compound: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn one_hot_enums() {
+        let types = Types::default().with("test1::State", TypeCategory::Enum,
+            Some(enumeration("test1::State", 3, &[(1, "Idle"), (2, "Busy"), (4, "Done")], true)));
+        let busy = types.translate("test1::State", "010");
+        assert_eq!(shown(&busy), "Busy");
+        assert!(matches!(busy.kind, ValueKind::Normal));
+
+        // No bit or several bits set is not a valid state
+        let none = types.translate("test1::State", "000");
+        assert_eq!(shown(&none), "Unknown(0)");
+        assert!(matches!(none.kind, ValueKind::Error));
+        let several = types.translate("test1::State", "101");
+        assert_eq!(shown(&several), "Idle|Done");
+        assert!(matches!(several.kind, ValueKind::Error));
+
+        assert_eq!(shown(&types.translate("test1::State", "0x1")), "x");
+        assert!(matches!(types.translate("test1::State", "z00").kind, ValueKind::HighImp));
+    }
+
+    #[test]
+    fn member_kinds_reach_the_result() {
+        let mut state = enumeration("test1::State", 2, &[(0, "Idle"), (1, "Busy"), (2, "Halt")], false);
+        state.enum_definition.as_mut().unwrap().member_kinds.insert("Halt".to_string(), ValueKind::Error);
+        let mut op = op_union();
+        op.union_definition.as_mut().unwrap().member_kinds.insert("Nop".to_string(), ValueKind::DontCare);
+        let types = ops()
+            .with("test1::State", TypeCategory::Enum, Some(state))
+            .with("test1::Op", TypeCategory::Union, Some(op));
+
+        assert!(matches!(types.translate("test1::State", "10").kind, ValueKind::Error));
+        assert!(matches!(types.translate("test1::State", "01").kind, ValueKind::Normal));
+        let nop = types.translate("test1::Op", "10_0000_0000");
+        assert!(matches!(nop.kind, ValueKind::DontCare));
+        assert!(matches!(subfield(&nop, "tag").kind, ValueKind::DontCare));
+        assert!(matches!(types.translate("test1::Op", "00_0000_0000").kind, ValueKind::Normal));
+    }
+
+    #[test]
+    fn fixed_point() {
+        assert_eq!(shown(&translate_fixed_point(&digits("0001_1000"), 4)), "1.5");
+        // Two's complement: -2.0 and the smallest negative step
+        assert_eq!(shown(&translate_fixed_point(&digits("1110_0000"), 4)), "-2.0");
+        assert_eq!(shown(&translate_fixed_point(&digits("1111_1111"), 4)), "-0.0625");
+        // The most negative value has no positive counterpart
+        assert_eq!(shown(&translate_fixed_point(&digits("1000_0000"), 4)), "-8.0");
+        // More fraction bits than an f64 holds round to the nearest double
+        assert_eq!(shown(&translate_fixed_point(&digits(&format!("0{}", "1".repeat(64))), 64)), "1.0");
+        assert_eq!(shown(&translate_fixed_point(&digits("01x0_0000"), 4)), "x");
+    }
+
+    #[test]
+    fn floats() {
+        let float = |bits: &str| shown(&translate_float(&digits(bits), 8, 23));
+        assert_eq!(float("0_01111111_00000000000000000000000"), "1.0");
+        assert_eq!(float("1_10000000_10000000000000000000000"), "-3.0");
+        assert_eq!(float("0_11111111_00000000000000000000000"), "inf");
+        assert_eq!(float("1_11111111_00000000000000000000000"), "-inf");
+        assert_eq!(float("0_11111111_10000000000000000000000"), "NaN");
+        assert_eq!(float("0_00000000_00000000000000000000001"), "1e-45");
+
+        // Other layouts are decoded field by field: FloatingPoint#(5, 10)
+        let half = |bits: &str| shown(&translate_float(&digits(bits), 5, 10));
+        assert_eq!(half("0_01111_0000000000"), "1.0");
+        assert_eq!(half("1_10000_1000000000"), "-3.0");
+        assert_eq!(half("1_11111_0000000000"), "-inf");
+        assert_eq!(half("0_11111_0000000001"), "NaN");
+        // Denormals: no implicit leading one
+        assert_eq!(half("0_00000_0000000001"), format!("{:?}", 2f64.powi(-24)));
+        assert_eq!(half("0_00000_1000000000"), format!("{:?}", 2f64.powi(-15)));
+
+        // A width that doesn't match the layout stays bits
+        assert_eq!(translate_float(&digits("0101"), 5, 10).val, ValueRepr::Bits(4, "0101".to_string()));
+    }
+
+    #[test]
+    fn bools() {
+        let types = Types::default()
+            .with("test1::Flags", TypeCategory::Struct, Some(layout(2, vec![field("on", 1, 1, "Bool"), field("off", 0, 0, "Bool")])))
+            .with("Bool", TypeCategory::Bool, None);
+        let flags = types.translate("test1::Flags", "10");
+        assert_eq!(shown(subfield(&flags, "on")), "True");
+        assert_eq!(shown(subfield(&flags, "off")), "False");
+        assert!(matches!(subfield(&flags, "off").kind, ValueKind::Weak));
+
+        // A top-level Bool stays a line
+        let line = |value: &str| translate_bool_line(&VariableValue::String(value.to_string()));
+        assert_eq!(line("1").val, ValueRepr::Bit('1'));
+        assert!(matches!(line("1").kind, ValueKind::Normal));
+        assert_eq!(line("0").val, ValueRepr::Bit('0'));
+        assert!(matches!(line("0").kind, ValueKind::Normal));
+        assert_eq!(line("x").val, ValueRepr::Bit('x'));
+        assert!(matches!(line("x").kind, ValueKind::Undef));
+        assert_eq!(line("z").val, ValueRepr::Bit('z'));
+        assert!(matches!(line("z").kind, ValueKind::HighImp));
+    }
+
+    #[test]
+    fn fshow_summaries() {
+        let types = Types::default()
+            .with("test1::Foo_st", TypeCategory::Struct, Some(layout(6, vec![field("rgb", 5, 4, "test1::Color"), field("b", 3, 0, "Bit#(4)")])))
+            .with("test1::Color", TypeCategory::Enum, Some(enumeration("test1::Color", 2, &[(0, "Red"), (1, "Green"), (2, "Blue")], false)))
+            .with("Vector#(100, Bit#(8))", TypeCategory::Vector, Some(vector(100, "Bit#(8)", 8)));
+        assert_eq!(shown(&types.translate("test1::Foo_st", "10_0011")), "Foo_st { rgb: Blue, b: 'h3 }");
+
+        // Long values are cut off, ending in `...`
+        let long = shown(&types.translate("Vector#(100, Bit#(8))", &"0".repeat(800)));
+        assert_eq!(long.chars().count(), SUMMARY_MAX_CHARS);
+        assert!(long.starts_with("<V 'h00 'h00 "));
+        assert!(long.ends_with("..."));
+        assert_eq!(truncate_summary("<V 'h00 >".to_string()), "<V 'h00 >");
+    }
+
+    #[test]
+    fn maybe() {
+        let types = Types::default()
+            .with("Maybe#(UInt#(4))", TypeCategory::Maybe, None)
+            .with("UInt#(4)", TypeCategory::UInt, None);
+        let invalid = types.translate("Maybe#(UInt#(4))", "0_1010");
+        assert_eq!(shown(&invalid), "Invalid");
+        assert!(matches!(invalid.kind, ValueKind::Undef));
+        assert_eq!(shown(&types.translate("Maybe#(UInt#(4))", "1_0101")), "5");
+        assert_eq!(shown(&types.translate("Maybe#(UInt#(4))", "x_0101")), "x");
+        // Only the valid bit has to be known for Invalid
+        assert_eq!(shown(&types.translate("Maybe#(UInt#(4))", "0_xxxx")), "Invalid");
+    }
+
+    #[test]
+    fn combined_complex() {
+        let types = Types::default()
+            .with("Complex#(Int#(4))", TypeCategory::Struct, Some(layout(8, vec![field("rel", 7, 4, "Int#(4)"), field("img", 3, 0, "Int#(4)")])))
+            .with("Int#(4)", TypeCategory::Int, None);
+        BSV_FORMATS.write().unwrap().complex_combined = true;
+        assert_eq!(shown(&types.translate("Complex#(Int#(4))", "0011_1110")), "3 - 2i");
+        assert_eq!(shown(&types.translate("Complex#(Int#(4))", "1111_0010")), "-1 + 2i");
+        assert_eq!(shown(&types.translate("Complex#(Int#(4))", "1000_0000")), "-8 + 0i");
+    }
 }
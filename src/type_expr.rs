@@ -0,0 +1,394 @@
+// Copyright: Copyright (c) 2025 Dyumnin Semiconductors. All rights reserved.
+// Author: Vijayvithal <jahagirdar.vs@gmail.com>
+// Created on: 2026-10-17
+// Description: Parser for BSV type expressions as they appear in bluespec.json.
+// =========================================================================
+// src/type_expr.rs
+// =========================================================================
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::helper::TypeStructure;
+
+// -------------------------------------------------------------------------
+// type_expr.rs: BSV Type Expressions
+// -------------------------------------------------------------------------
+
+/// A parsed BSV type such as `test1::Foo_st`, `Bit#(8)` or `Maybe#(type a)`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeExpr {
+    /// A type constructor applied to its arguments (none for plain names like `Bool`).
+    Constructor {
+        package: Option<String>,
+        name: String,
+        args: Vec<TypeExpr>,
+    },
+    /// A numeric argument: the `8` in `Bit#(8)`.
+    Numeric(usize),
+    /// A polymorphic argument bluetcl left unresolved: `type a`, `numeric type a`.
+    Variable { numeric: bool, name: String },
+}
+
+impl TypeExpr {
+    /// Parses a complete type expression; trailing garbage is an error.
+    pub fn parse(text: &str) -> Option<TypeExpr> {
+        let mut rest = text;
+        let expr = parse_expr(&mut rest)?;
+        rest.trim().is_empty().then_some(expr)
+    }
+
+    /// `Bit#(width)`, the type of an otherwise unknown field.
+    pub fn bits(width: usize) -> TypeExpr {
+        TypeExpr::Constructor {
+            package: None,
+            name: "Bit".to_string(),
+            args: vec![TypeExpr::Numeric(width)],
+        }
+    }
+
+    /// Constructor name without its package qualifier.
+    pub fn base_name(&self) -> Option<&str> {
+        match self {
+            TypeExpr::Constructor { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    pub fn args(&self) -> &[TypeExpr] {
+        match self {
+            TypeExpr::Constructor { args, .. } => args,
+            _ => &[],
+        }
+    }
+
+    /// True when no polymorphic variables remain anywhere in the expression.
+    pub fn is_concrete(&self) -> bool {
+        match self {
+            TypeExpr::Constructor { args, .. } => args.iter().all(TypeExpr::is_concrete),
+            TypeExpr::Numeric(_) => true,
+            TypeExpr::Variable { .. } => false,
+        }
+    }
+
+    /// Element types of a `Tuple2#(...)`..`Tuple8#(...)`, first element first.
+    pub fn tuple_elements(&self) -> Option<&[TypeExpr]> {
+        let arity: usize = self.base_name()?.strip_prefix("Tuple")?.parse().ok()?;
+        let args = self.args();
+        ((2..=8).contains(&arity) && args.len() == arity).then_some(args)
+    }
+
+    /// Length and element type of a `Vector#(n, t)`.
+    pub fn vector_parts(&self) -> Option<(usize, &TypeExpr)> {
+        match (self.base_name()?, self.args()) {
+            ("Vector", [TypeExpr::Numeric(n), element]) => Some((*n, element)),
+            _ => None,
+        }
+    }
+
+    /// Type of the parts of a `Complex#(t)`.
+    pub fn complex_part(&self) -> Option<&TypeExpr> {
+        match (self.base_name()?, self.args()) {
+            ("Complex", [part]) => Some(part),
+            _ => None,
+        }
+    }
+
+    /// Left and right types of an `Either#(a, b)`.
+    pub fn either_parts(&self) -> Option<(&TypeExpr, &TypeExpr)> {
+        match (self.base_name()?, self.args()) {
+            ("Either", [left, right]) => Some((left, right)),
+            _ => None,
+        }
+    }
+
+    /// Integer and fractional bit counts of a `FixedPoint#(i, f)`.
+    pub fn fixed_point_parts(&self) -> Option<(usize, usize)> {
+        match (self.base_name()?, self.args()) {
+            ("FixedPoint", [TypeExpr::Numeric(i), TypeExpr::Numeric(f)]) => Some((*i, *f)),
+            _ => None,
+        }
+    }
+
+    /// Exponent and mantissa bit counts of a `FloatingPoint#(e, m)` or one of its
+    /// `FloatingPoint.bsv` aliases.
+    pub fn float_parts(&self) -> Option<(usize, usize)> {
+        match (self.base_name()?, self.args()) {
+            ("FloatingPoint", [TypeExpr::Numeric(e), TypeExpr::Numeric(m)]) => Some((*e, *m)),
+            ("Half", []) => Some((5, 10)),
+            ("Float", []) => Some((8, 23)),
+            ("Double", []) => Some((11, 52)),
+            _ => None,
+        }
+    }
+
+    /// Bit width, from a typedef or else from the structure of the expression.
+    pub fn width(&self, bsv_typedefs: &HashMap<String, TypeStructure>) -> Option<usize> {
+        if let Some(def) = bsv_typedefs.get(&self.to_string()) {
+            return Some(def.total_width);
+        }
+        if let Some(elements) = self.tuple_elements() {
+            return elements.iter().map(|e| e.width(bsv_typedefs)).sum();
+        }
+        if let Some((length, element)) = self.vector_parts() {
+            return Some(length * element.width(bsv_typedefs)?);
+        }
+        if let Some(part) = self.complex_part() {
+            return Some(2 * part.width(bsv_typedefs)?);
+        }
+        if let Some((left, right)) = self.either_parts() {
+            return Some(1 + left.width(bsv_typedefs)?.max(right.width(bsv_typedefs)?));
+        }
+        if let Some((i, f)) = self.fixed_point_parts() {
+            return Some(i + f);
+        }
+        if let Some((e, m)) = self.float_parts() {
+            return Some(1 + e + m);
+        }
+        match (self.base_name()?, self.args()) {
+            ("Bool", []) => Some(1),
+            ("void", []) => Some(0),
+            ("Bit" | "Int" | "UInt", [TypeExpr::Numeric(n)]) => Some(*n),
+            ("Maybe", [payload]) => payload.width(bsv_typedefs).map(|w| w + 1),
+            _ => None,
+        }
+    }
+
+    /// Substitutes the polymorphic variables so that the type is `width` bits wide.
+    /// Variables that stand for a whole type become `Bit#(n)`: with nothing more to go
+    /// on, the raw bits are the most honest rendering.
+    pub fn instantiate(&self, width: usize, bsv_typedefs: &HashMap<String, TypeStructure>) -> Option<TypeExpr> {
+        if self.is_concrete() {
+            return Some(self.clone());
+        }
+        let TypeExpr::Constructor { package, name, args } = self else {
+            return match self {
+                TypeExpr::Variable { numeric: false, .. } => Some(TypeExpr::bits(width)),
+                _ => None,
+            };
+        };
+        let applied = |args: Vec<TypeExpr>| TypeExpr::Constructor {
+            package: package.clone(),
+            name: name.clone(),
+            args,
+        };
+
+        if self.tuple_elements().is_some() {
+            // At most one element may be unknown: it takes whatever width is left over.
+            let known: usize = args.iter().filter(|a| a.is_concrete())
+                .map(|a| a.width(bsv_typedefs)).sum::<Option<usize>>()?;
+            let remaining = width.checked_sub(known)?;
+            let unknown = args.iter().filter(|a| !a.is_concrete()).count();
+            if unknown != 1 {
+                return None;
+            }
+            let args = args.iter()
+                .map(|a| if a.is_concrete() { Some(a.clone()) } else { a.instantiate(remaining, bsv_typedefs) })
+                .collect::<Option<Vec<_>>>()?;
+            return Some(applied(args));
+        }
+
+        match (name.as_str(), args.as_slice()) {
+            ("Bit" | "Int" | "UInt", [TypeExpr::Variable { .. }]) => Some(applied(vec![TypeExpr::Numeric(width)])),
+            ("Maybe", [payload]) => {
+                let payload = payload.instantiate(width.checked_sub(1)?, bsv_typedefs)?;
+                Some(applied(vec![payload]))
+            }
+            ("Vector", [TypeExpr::Numeric(length), element]) if *length > 0 && width.is_multiple_of(*length) => {
+                let element = element.instantiate(width / length, bsv_typedefs)?;
+                Some(applied(vec![TypeExpr::Numeric(*length), element]))
+            }
+            ("Vector", [TypeExpr::Variable { .. }, element]) if element.is_concrete() => {
+                let element_width = element.width(bsv_typedefs).filter(|w| *w > 0)?;
+                width.is_multiple_of(element_width).then(|| applied(vec![TypeExpr::Numeric(width / element_width), element.clone()]))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TypeExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeExpr::Constructor { package, name, args } => {
+                if let Some(package) = package {
+                    write!(f, "{}::", package)?;
+                }
+                write!(f, "{}", name)?;
+                if !args.is_empty() {
+                    let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+                    write!(f, "#({})", args.join(", "))?;
+                }
+                Ok(())
+            }
+            TypeExpr::Numeric(n) => write!(f, "{}", n),
+            TypeExpr::Variable { numeric: true, name } => write!(f, "numeric type {}", name),
+            TypeExpr::Variable { numeric: false, name } => write!(f, "type {}", name),
+        }
+    }
+}
+
+// --- Recursive Descent Parser ---
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '$'
+}
+
+fn take_ident<'a>(rest: &mut &'a str) -> Option<&'a str> {
+    let end = rest.find(|c: char| !is_ident_char(c)).unwrap_or(rest.len());
+    if end == 0 {
+        return None;
+    }
+    let (ident, tail) = rest.split_at(end);
+    *rest = tail;
+    Some(ident)
+}
+
+fn take_keyword(rest: &mut &str, keyword: &str) -> bool {
+    match rest.strip_prefix(keyword) {
+        Some(tail) if tail.starts_with(char::is_whitespace) => {
+            *rest = tail.trim_start();
+            true
+        }
+        _ => false,
+    }
+}
+
+fn parse_expr(rest: &mut &str) -> Option<TypeExpr> {
+    *rest = rest.trim_start();
+
+    if rest.starts_with(|c: char| c.is_ascii_digit()) {
+        let digits = take_ident(rest)?;
+        return digits.parse().ok().map(TypeExpr::Numeric);
+    }
+    if take_keyword(rest, "numeric") {
+        if !take_keyword(rest, "type") {
+            return None;
+        }
+        return Some(TypeExpr::Variable { numeric: true, name: take_ident(rest)?.to_string() });
+    }
+    if take_keyword(rest, "type") {
+        return Some(TypeExpr::Variable { numeric: false, name: take_ident(rest)?.to_string() });
+    }
+
+    // Qualified name: `test1::Foo_st` -> package `test1`, name `Foo_st`
+    let mut parts = vec![take_ident(rest)?];
+    while let Some(tail) = rest.strip_prefix("::") {
+        *rest = tail;
+        parts.push(take_ident(rest)?);
+    }
+    let name = parts.pop()?.to_string();
+    let package = (!parts.is_empty()).then(|| parts.join("::"));
+
+    let mut args = Vec::new();
+    if let Some(tail) = rest.strip_prefix("#(") {
+        *rest = tail;
+        loop {
+            args.push(parse_expr(rest)?);
+            *rest = rest.trim_start();
+            if let Some(tail) = rest.strip_prefix(',') {
+                *rest = tail;
+            } else if let Some(tail) = rest.strip_prefix(')') {
+                *rest = tail;
+                break;
+            } else {
+                return None;
+            }
+        }
+    }
+
+    Some(TypeExpr::Constructor { package, name, args })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typedefs(entries: &[(&str, usize)]) -> HashMap<String, TypeStructure> {
+        entries.iter().map(|(name, width)| (name.to_string(), TypeStructure {
+            total_width: *width,
+            segments: vec![],
+            enum_definition: None,
+            union_definition: None,
+            vector_definition: None,
+        })).collect()
+    }
+
+    #[test]
+    fn parses_and_prints_back() {
+        for text in [
+            "Bool",
+            "Bit#(8)",
+            "test1::Foo_st",
+            "Maybe#(type a)",
+            "Bit#(numeric type n)",
+            "Tuple3#(Bool, Int#(4), Tuple2#(Bit#(2), test1::Stage))",
+            "Complex::Complex#(FixedPoint::FixedPoint#(1, 15))",
+        ] {
+            assert_eq!(TypeExpr::parse(text).unwrap().to_string(), text);
+        }
+        // Whitespace is not significant
+        assert_eq!(TypeExpr::parse("Vector#( 4 ,Bit#(8) )").unwrap().to_string(), "Vector#(4, Bit#(8))");
+    }
+
+    #[test]
+    fn rejects_malformed_types() {
+        for text in ["", "Bit#(8", "Bit#(8))", "Bit#(8) x", "numeric a", "a::", "#(8)"] {
+            assert_eq!(TypeExpr::parse(text), None, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn qualified_names() {
+        let expr = TypeExpr::parse("test1::Foo_st").unwrap();
+        assert_eq!(expr.base_name(), Some("Foo_st"));
+        assert!(matches!(expr, TypeExpr::Constructor { package: Some(ref p), .. } if p == "test1"));
+    }
+
+    #[test]
+    fn widths_from_structure() {
+        let known = typedefs(&[("test1::Foo_st", 14)]);
+        let width = |text: &str| TypeExpr::parse(text).unwrap().width(&known);
+        assert_eq!(width("Bool"), Some(1));
+        assert_eq!(width("void"), Some(0));
+        assert_eq!(width("UInt#(7)"), Some(7));
+        assert_eq!(width("Maybe#(Bit#(8))"), Some(9));
+        assert_eq!(width("Vector#(3, test1::Foo_st)"), Some(42));
+        assert_eq!(width("Tuple2#(Bool, Int#(4))"), Some(5));
+        assert_eq!(width("Either#(Bit#(4), test1::Foo_st)"), Some(15));
+        assert_eq!(width("Complex#(FixedPoint#(1, 15))"), Some(32));
+        assert_eq!(width("Float"), Some(32));
+        assert_eq!(width("FloatingPoint#(11, 52)"), Some(64));
+        assert_eq!(width("test1::Unknown"), None);
+        assert_eq!(width("Bit#(numeric type n)"), None);
+    }
+
+    #[test]
+    fn kinds_of_type() {
+        let expr = TypeExpr::parse("Tuple2#(Bool, Bit#(3))").unwrap();
+        assert_eq!(expr.tuple_elements().map(|e| e.len()), Some(2));
+        assert!(TypeExpr::parse("Tuple9#(Bool, Bool, Bool, Bool, Bool, Bool, Bool, Bool, Bool)").unwrap().tuple_elements().is_none());
+        assert!(TypeExpr::parse("Tuple2#(Bool)").unwrap().tuple_elements().is_none());
+        let expr = TypeExpr::parse("Vector#(4, Bit#(8))").unwrap();
+        assert_eq!(expr.vector_parts().map(|(n, e)| (n, e.to_string())), Some((4, "Bit#(8)".to_string())));
+        assert_eq!(TypeExpr::parse("FixedPoint#(2, 14)").unwrap().fixed_point_parts(), Some((2, 14)));
+        assert_eq!(TypeExpr::parse("Half").unwrap().float_parts(), Some((5, 10)));
+        assert!(!TypeExpr::parse("Maybe#(type a)").unwrap().is_concrete());
+        assert!(TypeExpr::parse("Maybe#(Bool)").unwrap().is_concrete());
+    }
+
+    #[test]
+    fn instantiates_from_width() {
+        let known = typedefs(&[("test1::Foo_st", 14)]);
+        let instantiate = |text: &str, width| TypeExpr::parse(text).unwrap().instantiate(width, &known).map(|e| e.to_string());
+        assert_eq!(instantiate("Bit#(numeric type a)", 8).as_deref(), Some("Bit#(8)"));
+        assert_eq!(instantiate("Maybe#(type a)", 9).as_deref(), Some("Maybe#(Bit#(8))"));
+        assert_eq!(instantiate("Vector#(4, type a)", 32).as_deref(), Some("Vector#(4, Bit#(8))"));
+        assert_eq!(instantiate("Vector#(numeric type n, test1::Foo_st)", 28).as_deref(), Some("Vector#(2, test1::Foo_st)"));
+        assert_eq!(instantiate("Vector#(numeric type n, test1::Foo_st)", 27), None);
+        assert_eq!(instantiate("Tuple2#(Bool, type a)", 5).as_deref(), Some("Tuple2#(Bool, Bit#(4))"));
+        assert_eq!(instantiate("Tuple2#(type a, type b)", 5), None);
+        // Concrete types are left alone
+        assert_eq!(instantiate("Bit#(3)", 8).as_deref(), Some("Bit#(3)"));
+    }
+}
@@ -0,0 +1,352 @@
+// Copyright: Copyright (c) 2025 Dyumnin Semiconductors. All rights reserved.
+// Author: Vijayvithal <jahagirdar.vs@gmail.com>
+// Created on: 2026-10-17
+// Description: Reader for the Verilog bsc generates, for instance and port tables.
+// =========================================================================
+// src/verilog.rs
+// =========================================================================
+
+use std::collections::HashMap;
+
+// -------------------------------------------------------------------------
+// verilog.rs: Submodule Instances from bsc Verilog
+// -------------------------------------------------------------------------
+
+/// A submodule instance of a generated module.
+#[derive(Debug, Clone)]
+pub struct VerilogInstance {
+    pub name: String,
+    // `FIFO2`, `mkA`, or `RegN` for registers bsc inlined
+    pub module_type: String,
+    // Port names with their widths, when the connected signal is declared or a sized constant
+    pub ports: Vec<(String, Option<usize>)>,
+    // Registers bsc wrote as a `reg` rather than instantiating RegN/RegA/RegUN
+    pub inlined: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct VerilogModule {
+    pub name: String,
+    pub instances: Vec<VerilogInstance>,
+}
+
+// Statements that start with an identifier but are not instantiations
+const KEYWORDS: [&str; 22] = [
+    "module", "endmodule", "input", "output", "inout", "wire", "reg", "assign", "always", "initial",
+    "begin", "end", "if", "else", "case", "casez", "endcase", "default", "integer", "parameter",
+    "localparam", "generate",
+];
+
+// `.PORT(expression)` pairs of an instantiation, the expression as tokens
+type Connections = Vec<(String, Vec<String>)>;
+
+fn tokenize(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+        } else if c.is_ascii_alphanumeric() || matches!(c, '_' | '$' | '\'' | '`') {
+            // Identifiers, numbers, sized literals and `BSV_ASSIGNMENT_DELAY style macros
+            let start = i;
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || matches!(chars[i], '_' | '$' | '\'')) {
+                i += 1;
+            }
+            tokens.push(chars[start..i].iter().collect());
+        } else {
+            tokens.push(c.to_string());
+            i += 1;
+        }
+    }
+    tokens
+}
+
+fn is_ident(token: &str) -> bool {
+    token.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+}
+
+/// Width of a sized literal: `1'b0` -> 1, `32'd5` -> 32.
+fn literal_width(token: &str) -> Option<usize> {
+    token.split_once('\'').and_then(|(width, _)| width.parse().ok())
+}
+
+/// Index just past the bracket that closes the one at `open`, or the end of the tokens
+/// when the input stops first.
+fn skip_balanced(tokens: &[String], open: usize) -> usize {
+    let (open_token, close_token) = match tokens.get(open).map(String::as_str) {
+        Some("(") => ("(", ")"),
+        Some("[") => ("[", "]"),
+        Some(_) => ("{", "}"),
+        None => return tokens.len(),
+    };
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        if token == open_token {
+            depth += 1;
+        } else if token == close_token {
+            depth -= 1;
+            if depth == 0 {
+                return i + 1;
+            }
+        }
+    }
+    tokens.len()
+}
+
+/// `wire [4 : 0] a, b;` -> widths of `a` and `b`. Starts at the keyword; returns the index
+/// after `;` and the declared names.
+fn parse_declaration(tokens: &[String], mut i: usize, widths: &mut HashMap<String, usize>) -> (usize, Vec<String>) {
+    i += 1;
+    let mut width = 1;
+    while i < tokens.len() && matches!(tokens[i].as_str(), "signed" | "wire" | "reg") {
+        i += 1;
+    }
+    if tokens.get(i).map(String::as_str) == Some("[") {
+        let close = skip_balanced(tokens, i);
+        // bsc always writes `[msb : lsb]` with plain numbers
+        if let [_, msb, colon, lsb, _] = &tokens[i..close]
+            && let (Ok(msb), ":", Ok(lsb)) = (msb.parse::<usize>(), colon.as_str(), lsb.parse::<usize>())
+        {
+            width = msb.abs_diff(lsb) + 1;
+        }
+        i = close;
+    }
+    // Names up to an initialiser (`wire x = ...;`) or the end of the declaration
+    let mut names = Vec::new();
+    while i < tokens.len() && tokens[i] != ";" && tokens[i] != "=" {
+        if is_ident(&tokens[i]) {
+            widths.insert(tokens[i].clone(), width);
+            names.push(tokens[i].clone());
+        }
+        i += 1;
+    }
+    while i < tokens.len() && tokens[i] != ";" {
+        i += 1;
+    }
+    (i + 1, names)
+}
+
+/// `FIFO2 #(.width(32'd5)) fifo(.CLK(CLK), .D_IN(fifo$D_IN));` starting at the module type.
+/// Returns the instance (ports not yet sized) and the index after `;`, or `None` if this
+/// isn't an instantiation.
+fn parse_instance(tokens: &[String], start: usize) -> Option<(VerilogInstance, Connections, usize)> {
+    let module_type = tokens.get(start)?;
+    let mut i = start + 1;
+    if tokens.get(i)? == "#" {
+        i = skip_balanced(tokens, i + 1);
+    }
+    let name = tokens.get(i)?;
+    if !is_ident(name) || tokens.get(i + 1)? != "(" || tokens.get(i + 2)? != "." {
+        return None;
+    }
+    let end = skip_balanced(tokens, i + 1);
+    let mut connections = Vec::new();
+    let mut j = i + 2;
+    while j + 2 < end && tokens[j] == "." {
+        let port = tokens[j + 1].clone();
+        let close = skip_balanced(tokens, j + 2);
+        // A file cut off inside `.PORT(`
+        if close <= j + 3 {
+            return None;
+        }
+        connections.push((port, tokens[j + 3..close - 1].to_vec()));
+        j = close;
+        if tokens.get(j).map(String::as_str) == Some(",") {
+            j += 1;
+        }
+    }
+    let instance = VerilogInstance { name: name.clone(), module_type: module_type.clone(), ports: vec![], inlined: false };
+    Some((instance, connections, end + 1))
+}
+
+/// Reads every module in a bsc-generated Verilog file.
+pub fn parse_verilog(text: &str) -> Vec<VerilogModule> {
+    let tokens = tokenize(text);
+    let mut modules = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        if tokens[i] != "module" {
+            i += 1;
+            continue;
+        }
+        let name = tokens.get(i + 1).cloned().unwrap_or_default();
+        let end = tokens.iter().skip(i).position(|t| t == "endmodule").map_or(tokens.len(), |p| i + p);
+
+        let mut widths: HashMap<String, usize> = HashMap::new();
+        let mut registers: Vec<String> = Vec::new();
+        let mut instances = Vec::new();
+        // Skip the port list of the module header
+        let mut j = tokens.iter().skip(i).position(|t| t == ";").map_or(end, |p| i + p + 1);
+        while j < end {
+            let token = tokens[j].as_str();
+            if matches!(token, "input" | "output" | "inout" | "wire" | "reg") {
+                let (next, names) = parse_declaration(&tokens, j, &mut widths);
+                if token == "reg" {
+                    registers.extend(names);
+                }
+                j = next;
+            } else if is_ident(token) && !KEYWORDS.contains(&token) {
+                match parse_instance(&tokens, j) {
+                    Some((instance, connections, next)) => {
+                        instances.push((instance, connections));
+                        j = next;
+                    }
+                    None => j += 1,
+                }
+            } else {
+                j += 1;
+            }
+        }
+
+        let mut module = VerilogModule { name, instances: vec![] };
+        for (mut instance, connections) in instances {
+            instance.ports = connections.into_iter().map(|(port, expr)| {
+                // Sized from the connected signal or constant, else from bsc's `inst$PORT` wire
+                let width = match expr.as_slice() {
+                    [signal] => widths.get(signal).copied().or_else(|| literal_width(signal)),
+                    _ => None,
+                }.or_else(|| widths.get(&format!("{}${}", instance.name, port)).copied());
+                (port, width)
+            }).collect();
+            module.instances.push(instance);
+        }
+        // An inlined register leaves `reg r;` plus its `r$D_IN` and `r$EN` wires
+        for register in registers {
+            let width = widths.get(&register).copied();
+            if widths.contains_key(&format!("{}$D_IN", register)) && widths.contains_key(&format!("{}$EN", register)) {
+                module.instances.push(VerilogInstance {
+                    ports: vec![("D_IN".to_string(), width), ("EN".to_string(), Some(1)), ("Q_OUT".to_string(), width)],
+                    name: register,
+                    module_type: "RegN".to_string(),
+                    inlined: true,
+                });
+            }
+        }
+        modules.push(module);
+        i = end + 1;
+    }
+    modules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MK_TOP: &str = "
+`ifdef BSV_ASSIGNMENT_DELAY
+`else
+  `define BSV_ASSIGNMENT_DELAY
+`endif
+module mkTop(CLK, RST_N, out);
+  input  CLK;
+  input  RST_N;
+  output [7 : 0] out;
+
+  // register rb
+  reg [9 : 0] rb;
+  wire [9 : 0] rb$D_IN;
+  wire rb$EN;
+
+  // register plain, no D_IN/EN wires
+  reg [3 : 0] plain;
+
+  // ports of submodule aa_inst_a
+  wire [31 : 0] aa_inst_a$in_x;
+  wire [7 : 0] aa_inst_a$in_b, aa_inst_a$out;
+  wire aa_inst_a$EN_in;
+
+  wire [4 : 0] fifo$D_IN, fifo$D_OUT;
+
+  mkA aa_inst_a(.CLK(CLK), .RST_N(RST_N), .in_x(aa_inst_a$in_x), .in_b(aa_inst_a$in_b),
+                .EN_in(aa_inst_a$EN_in), .out(aa_inst_a$out));
+
+  FIFO2 #(.width(32'd5), .guarded(1'd1)) fifo(.RST(RST_N), .CLK(CLK),
+      .D_IN(fifo$D_IN), .ENQ(1'b0), .CLR(1'b0), .D_OUT(fifo$D_OUT), .FULL_N(), .EMPTY_N());
+
+  always@(posedge CLK)
+    if (rb$EN) rb <= `BSV_ASSIGNMENT_DELAY rb$D_IN;
+endmodule
+";
+
+    fn instance<'a>(module: &'a VerilogModule, name: &str) -> &'a VerilogInstance {
+        module.instances.iter().find(|i| i.name == name).unwrap_or_else(|| panic!("no instance {}", name))
+    }
+
+    fn port(instance: &VerilogInstance, name: &str) -> Option<usize> {
+        instance.ports.iter().find(|(p, _)| p == name).unwrap_or_else(|| panic!("no port {}", name)).1
+    }
+
+    #[test]
+    fn tokenizes_macros_and_comments() {
+        assert_eq!(tokenize("`define X /* c */ a$b[3 : 0] // end"), vec!["`define", "X", "a$b", "[", "3", ":", "0", "]"]);
+        assert_eq!(tokenize("8'hff `BSV_ASSIGNMENT_DELAY"), vec!["8'hff", "`BSV_ASSIGNMENT_DELAY"]);
+    }
+
+    #[test]
+    fn reads_instances_and_port_widths() {
+        let modules = parse_verilog(MK_TOP);
+        assert_eq!(modules.len(), 1);
+        let top = &modules[0];
+        assert_eq!(top.name, "mkTop");
+
+        let a = instance(top, "aa_inst_a");
+        assert_eq!(a.module_type, "mkA");
+        assert!(!a.inlined);
+        assert_eq!(port(a, "in_x"), Some(32));
+        assert_eq!(port(a, "in_b"), Some(8));
+        assert_eq!(port(a, "out"), Some(8));
+        assert_eq!(port(a, "EN_in"), Some(1));
+        assert_eq!(port(a, "CLK"), Some(1));
+
+        // Parameters are skipped; constants are sized by their literal
+        let fifo = instance(top, "fifo");
+        assert_eq!(fifo.module_type, "FIFO2");
+        assert_eq!(port(fifo, "D_OUT"), Some(5));
+        assert_eq!(port(fifo, "CLR"), Some(1));
+        // Nothing connected: nothing to size it by
+        assert_eq!(port(fifo, "FULL_N"), None);
+    }
+
+    #[test]
+    fn inlined_registers() {
+        let top = &parse_verilog(MK_TOP)[0];
+        let rb = instance(top, "rb");
+        assert!(rb.inlined);
+        assert_eq!(rb.module_type, "RegN");
+        assert_eq!(rb.ports, vec![("D_IN".to_string(), Some(10)), ("EN".to_string(), Some(1)), ("Q_OUT".to_string(), Some(10))]);
+        // A reg without the D_IN/EN pair is not a register bsc inlined
+        assert!(!top.instances.iter().any(|i| i.name == "plain"));
+    }
+
+    #[test]
+    fn truncated_input() {
+        assert!(parse_verilog("module m(); FIFO2 #").iter().all(|m| m.instances.is_empty()));
+        assert!(parse_verilog("module m(); mkA a(.CLK(").iter().all(|m| m.instances.is_empty()));
+        // A partial file stops anywhere
+        for end in 0..MK_TOP.len() {
+            parse_verilog(&MK_TOP[..end]);
+        }
+    }
+
+    #[test]
+    fn several_modules() {
+        let text = "module a(); endmodule module b(); a x(.CLK(CLK)); endmodule";
+        let modules = parse_verilog(text);
+        assert_eq!(modules.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(), vec!["a", "b"]);
+        assert!(modules[0].instances.is_empty());
+        assert_eq!(instance(&modules[1], "x").ports, vec![("CLK".to_string(), None)]);
+    }
+}